@@ -1,4 +1,8 @@
+pub mod bvh_benchmark;
 pub mod circle_fill;
 pub mod clock;
+pub mod depth_of_field;
+pub mod global_illumination;
+pub mod motion_blur;
 pub mod projectile;
 pub mod shaded_circle;
@@ -0,0 +1,118 @@
+use crate::{
+    math::{Matrix4x4, Rng},
+    rendering::{
+        obj::{load_obj, parse_obj},
+        world::World,
+        Material, Object, Ray,
+    },
+};
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+// a large model to load, a generated one stands in when it isn't there
+const MESH: &str = "mesh.obj";
+// the generated terrain is a grid of this many squares a side, two triangles each
+const SIDE: usize = 224;
+// testing every triangle is slow, so few rays are enough to see the difference
+const RAYS: i32 = 500;
+
+pub fn run() -> std::io::Result<()> {
+    let start = Instant::now();
+    let triangles = match load_obj(MESH) {
+        Ok(triangles) => triangles,
+        Err(_) => parse_obj(&terrain())?,
+    };
+    let load = start.elapsed();
+
+    let mut world = World::new();
+    for triangle in triangles {
+        let mut object = Object::new_triangle(Material::default(), triangle, Matrix4x4::identity());
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+        world.objects.push(object);
+    }
+
+    // rays from all over the mesh's box, aimed at random points inside it
+    let bounds = world.bounds();
+    let mut rng = Rng::new(0);
+    let random_point = |rng: &mut Rng| {
+        let mut point = bounds.min;
+        for axis in 0..3 {
+            point.xyz[axis] += rng.next_f32() * (bounds.max.xyz[axis] - bounds.min.xyz[axis]);
+        }
+        point
+    };
+    let rays: Vec<Ray> = (0..RAYS)
+        .map(|_| {
+            let target = random_point(&mut rng);
+            let mut origin = random_point(&mut rng);
+            origin.xyz[1] = bounds.max.xyz[1] + 10.0;
+            Ray::point_vector(origin, (target - origin).normalize())
+        })
+        .collect();
+
+    let naive = time(&world, &rays);
+
+    let start = Instant::now();
+    world.build_bvh();
+    let build = start.elapsed();
+
+    let bvh = time(&world, &rays);
+
+    println!(
+        "{} triangles (loaded in {:?}), {} rays",
+        world.objects.len(),
+        load,
+        rays.len()
+    );
+    println!("naive: {:?}", naive);
+    println!("bvh: {:?} (built in {:?})", bvh, build);
+    println!("speedup: {:.1}x", naive.as_secs_f64() / bvh.as_secs_f64());
+
+    Ok(())
+}
+
+// rolling hills as obj text, so the benchmark goes through the same loader a real model would
+fn terrain() -> String {
+    let mut obj = String::new();
+    let height = |x: usize, z: usize| {
+        let (x, z) = (x as f32 * 0.1, z as f32 * 0.1);
+        x.sin() * z.cos() * 2.0 + (x * 0.37 + z * 0.23).sin() * 4.0
+    };
+
+    for z in 0..=SIDE {
+        for x in 0..=SIDE {
+            writeln!(obj, "v {} {} {}", x, height(x, z), z).unwrap();
+        }
+    }
+
+    // obj counts vertices from 1
+    let vertex = |x: usize, z: usize| z * (SIDE + 1) + x + 1;
+    for z in 0..SIDE {
+        for x in 0..SIDE {
+            writeln!(
+                obj,
+                "f {} {} {}\nf {} {} {}",
+                vertex(x, z),
+                vertex(x + 1, z),
+                vertex(x + 1, z + 1),
+                vertex(x, z),
+                vertex(x + 1, z + 1),
+                vertex(x, z + 1)
+            )
+            .unwrap();
+        }
+    }
+
+    obj
+}
+
+fn time(world: &World, rays: &[Ray]) -> Duration {
+    let start = Instant::now();
+    let hits: usize = rays.iter().map(|ray| world.intersect(ray).len()).sum();
+    let elapsed = start.elapsed();
+
+    // keeps both runs honest, they have to find the same intersections
+    println!("{} intersections", hits);
+    elapsed
+}
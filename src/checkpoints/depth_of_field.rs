@@ -0,0 +1,62 @@
+use crate::{
+    math::{Matrix4x4, Point, Vector},
+    rendering::{
+        antialiasing::{PixelFilter, Sampler},
+        camera::Camera,
+        renderer::Renderer,
+        world::World,
+        Color, Material, Object, PointLight,
+    },
+};
+use std::{f32::consts::PI, fs::File, io::Write};
+
+pub fn run() -> std::io::Result<()> {
+    let mut world = World::new();
+    world.lights.push(Box::new(PointLight::new(
+        Point::new(-10.0, 10.0, -10.0),
+        Color::new(1.0, 1.0, 1.0),
+    )));
+
+    // a row of spheres running away from the camera, only the middle one is in focus
+    for (i, color) in [
+        Color::new(1.0, 0.2, 0.0),
+        Color::new(0.2, 1.0, 0.2),
+        Color::new(0.2, 0.4, 1.0),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let mut sphere = Object::new_sphere(
+            Material::new(color, 0.1, 0.9, 0.9, 200.0),
+            Matrix4x4::identity().translate(i as f32 * 1.5 - 1.5, 0.0, i as f32 * 3.0),
+        );
+        sphere.calc_inverse_transform();
+        sphere.calc_inverse_transpose_transform();
+        world.objects.push(sphere);
+    }
+
+    let from = Point::new(0.0, 1.5, -6.0);
+    let focus = Point::new(0.0, 0.0, 3.0);
+    let mut camera = Camera::new(
+        400,
+        200,
+        PI / 3.0,
+        Matrix4x4::view_transform(from, focus, Vector::new(0.0, 1.0, 0.0)),
+    );
+    camera.aperture = 0.2;
+    camera.focal_distance = (focus - from).magnitude();
+
+    let mut renderer = Renderer::default();
+    renderer.samples = 16;
+    renderer.max_samples = 256;
+    renderer.sampler = Sampler::Sobol;
+    renderer.filter = PixelFilter::Gaussian;
+
+    let canvas = camera.render(&world, &renderer);
+
+    let mut output = File::create("depth_of_field.ppm")?;
+
+    output.write_all(canvas.to_ppm().as_bytes())?;
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use crate::{
+    math::{Matrix4x4, Point, Vector},
+    rendering::{
+        antialiasing::{PixelFilter, Sampler},
+        camera::Camera,
+        integrator::Integrator,
+        object_light::ObjectLight,
+        renderer::Renderer,
+        world::World,
+        Color, Material, Object,
+    },
+};
+use std::{f32::consts::PI, fs::File, io::Write};
+
+pub fn run() -> std::io::Result<()> {
+    let mut world = World::new();
+
+    let diffuse = |color| Material::new(color, 0.0, 1.0, 0.0, 1.0);
+    let wall = |x: f32, y: f32, z: f32| {
+        Matrix4x4::identity()
+            .translate(x, y, z)
+            .scale(1000.0, 1000.0, 1000.0)
+    };
+
+    // a box made of huge spheres, with red and green walls that bleed onto the others
+    let mut objects = vec![
+        Object::new_sphere(diffuse(Color::new(0.8, 0.1, 0.1)), wall(-1003.0, 0.0, 0.0)),
+        Object::new_sphere(diffuse(Color::new(0.1, 0.8, 0.1)), wall(1003.0, 0.0, 0.0)),
+        Object::new_sphere(diffuse(Color::new(0.8, 0.8, 0.8)), wall(0.0, -1001.0, 0.0)),
+        Object::new_sphere(diffuse(Color::new(0.8, 0.8, 0.8)), wall(0.0, 1005.0, 0.0)),
+        Object::new_sphere(diffuse(Color::new(0.8, 0.8, 0.8)), wall(0.0, 0.0, 1004.0)),
+        Object::new_sphere(
+            diffuse(Color::new(0.8, 0.8, 0.8)),
+            Matrix4x4::identity().translate(-1.0, 0.0, 1.0),
+        ),
+    ];
+
+    let mut mirror = Object::new_sphere(
+        diffuse(Color::new(0.0, 0.0, 0.0)),
+        Matrix4x4::identity()
+            .translate(1.3, -0.3, 0.0)
+            .scale(0.7, 0.7, 0.7),
+    );
+    mirror.material.reflective = 0.9;
+    objects.push(mirror);
+
+    let mut lamp = Object::new_sphere(
+        diffuse(Color::new(0.0, 0.0, 0.0)),
+        Matrix4x4::identity()
+            .translate(0.0, 4.0, 1.0)
+            .scale(0.6, 0.6, 0.6),
+    );
+    lamp.material.emission = Color::new(15.0, 15.0, 15.0);
+    objects.push(lamp);
+
+    for mut object in objects {
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+
+        if object.material.emission != Color::new(0.0, 0.0, 0.0) {
+            world
+                .lights
+                .push(Box::new(ObjectLight::new(world.objects.len(), 1)));
+        }
+        world.objects.push(object);
+    }
+    world.build_bvh();
+
+    let camera = Camera::new(
+        200,
+        200,
+        PI / 2.5,
+        Matrix4x4::view_transform(
+            Point::new(0.0, 1.5, -4.5),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+        ),
+    );
+
+    let mut renderer = Renderer::default();
+    renderer.samples = 16;
+    renderer.max_samples = 256;
+    renderer.variance_threshold = 0.01;
+    renderer.sampler = Sampler::Sobol;
+    renderer.filter = PixelFilter::Gaussian;
+    renderer.integrator = Integrator::PathTracer { max_bounces: 8 };
+
+    let canvas = camera.render(&world, &renderer);
+
+    let mut output = File::create("global_illumination.ppm")?;
+
+    output.write_all(canvas.to_ppm().as_bytes())?;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::{
+    math::{Matrix4x4, Point, Vector},
+    rendering::{
+        antialiasing::{PixelFilter, Sampler},
+        camera::Camera,
+        renderer::Renderer,
+        world::World,
+        Color, Material, Object, PointLight,
+    },
+};
+use std::{f32::consts::PI, fs::File, io::Write};
+
+pub fn run() -> std::io::Result<()> {
+    let mut world = World::new();
+    world.lights.push(Box::new(PointLight::new(
+        Point::new(-10.0, 10.0, -10.0),
+        Color::new(1.0, 1.0, 1.0),
+    )));
+
+    // one sphere slides across the frame while the other spins and grows in place
+    let mut sliding = Object::new_sphere(
+        Material::new(Color::new(1.0, 0.2, 0.0), 0.1, 0.9, 0.9, 200.0),
+        Matrix4x4::identity().translate(-2.5, 1.0, 0.0),
+    );
+    sliding.set_end_transform(Matrix4x4::identity().translate(-0.5, 1.0, 0.0));
+
+    let mut spinning = Object::new_sphere(
+        Material::new(Color::new(0.2, 0.4, 1.0), 0.1, 0.9, 0.9, 200.0),
+        Matrix4x4::identity()
+            .translate(1.5, -0.5, 0.0)
+            .scale(0.5, 1.0, 0.5),
+    );
+    spinning.set_end_transform(
+        Matrix4x4::identity()
+            .translate(1.5, -0.5, 0.0)
+            .rotate_z(PI / 2.0)
+            .scale(0.75, 1.5, 0.75),
+    );
+
+    for mut object in [sliding, spinning] {
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+        world.objects.push(object);
+    }
+    world.build_bvh();
+
+    let mut camera = Camera::new(
+        400,
+        200,
+        PI / 3.0,
+        Matrix4x4::view_transform(
+            Point::new(0.0, 0.0, -6.0),
+            Point::zero(),
+            Vector::new(0.0, 1.0, 0.0),
+        ),
+    );
+    camera.shutter_open = 0.0;
+    camera.shutter_close = 1.0;
+
+    let mut renderer = Renderer::default();
+    renderer.samples = 16;
+    renderer.max_samples = 256;
+    renderer.sampler = Sampler::Sobol;
+    renderer.filter = PixelFilter::Gaussian;
+
+    let canvas = camera.render(&world, &renderer);
+
+    let mut output = File::create("motion_blur.ppm")?;
+
+    output.write_all(canvas.to_ppm().as_bytes())?;
+
+    Ok(())
+}
@@ -1,12 +1,18 @@
-use unroll::unroll_for_loops;
-
 use crate::{
-    math::{Matrix4x4, Point},
-    rendering::{self, canvas::Canvas, object::Object, ray::Ray, Color, Material, PointLight},
+    math::{Matrix4x4, Point, Vector},
+    rendering::{
+        self,
+        antialiasing::{PixelFilter, Sampler},
+        camera::{Camera, Projection},
+        canvas::Canvas,
+        intersection::Intersection,
+        object::Object,
+        renderer::Renderer,
+        Color, LightSample, Material, PointLight,
+    },
 };
 use std::{fs::File, io::Write};
 
-#[unroll_for_loops]
 pub fn run() -> std::io::Result<()> {
     // let mut canvas = Canvas::new(100, 100, Color::new(0.0, 0.0, 0.0));
 
@@ -60,34 +66,68 @@ pub fn run() -> std::io::Result<()> {
     sphere.calc_inverse_transpose_transform();
     sphere.material.color = Color::new(1.0, 0.2, 0.0);
 
-    let light = PointLight::new(Point::new(0.0, 0.0, 2000.0), Color::new(1.0, 1.0, 1.0));
-
-    let mut hits = Vec::new();
-
-    for i in 0..canvas.width {
-        for j in 0..canvas.height {
-            let ray = Ray::new(i as f32, j as f32, 0.0, 0.0, 0.0, 1.0);
-            let intersection = ray.intersection(&mut sphere);
-            if let Some(mut x) = intersection {
-                if let Some(h) = Ray::hit(&mut x) {
-                    hits.push((ray, h.time));
-                }
+    // on the camera's side, so the light falls on the half of the sphere that's seen
+    let light = PointLight::new(Point::new(0.0, 0.0, -2000.0), Color::new(1.0, 1.0, 1.0));
+
+    // looks straight down +z with a view as wide as the canvas, so every world unit is one pixel.
+    // with y growing down the canvas that's a mirror image, which the flipped x undoes so
+    // pixel (i, j) sees world (i, j) like the rays above. it stands back from the sphere so
+    // the nearest hit is its front rather than the inside of its back
+    let mut camera = Camera::new(
+        canvas.width,
+        canvas.height,
+        0.0,
+        Matrix4x4::identity()
+            .scale(-1.0, 1.0, 1.0)
+            .mul(Matrix4x4::view_transform(
+                Point::new(500.0, 500.0, -1000.0),
+                Point::new(500.0, 500.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+            )),
+    );
+    camera.projection = Projection::Orthographic {
+        width: canvas.width as f32,
+    };
+
+    let mut renderer = Renderer::default();
+    renderer.samples = 4;
+    renderer.max_samples = 64;
+    renderer.sampler = Sampler::Jittered;
+    renderer.filter = PixelFilter::Tent;
+
+    let rays = renderer.render_sampled(&mut canvas, |x, y, rng| {
+        let ray = camera.ray_for_pixel(x, y, rng).unwrap();
+
+        let intersections = ray.intersection(&sphere);
+        match Intersection::hit(&intersections) {
+            Some(hit) => {
+                let point = ray.position_at(hit.time);
+                let normal = sphere.normal_at(point, ray.shutter_time);
+                let eye = ray.direction.negate();
+
+                rendering::lighting(
+                    &sphere.material,
+                    sphere.color_at(point, ray.shutter_time),
+                    &light,
+                    eye,
+                    normal,
+                    &[LightSample::towards(
+                        point,
+                        light.position,
+                        light.intensity,
+                        light.attenuation,
+                    )],
+                )
             }
+            None => Color::new(0.0, 0.0, 0.0),
         }
-    }
-
-    for x in &hits {
-        let point = x.0.position_at(x.1);
-        let normal = sphere.normal_at(point);
-        let eye = x.0.direction.negate();
+    });
 
-        canvas.pixels[point.xyz[0] as usize][point.xyz[1] as usize] =
-            rendering::lighting(sphere.material, light, point, eye, normal);
-    }
+    println!("{} rays traced", rays);
 
     let mut output = File::create("output.ppm")?;
 
-    output.write(canvas.to_ppm().as_bytes())?;
+    output.write_all(canvas.to_ppm().as_bytes())?;
 
     Ok(())
 }
@@ -1,10 +1,17 @@
+pub mod bounds;
 pub mod matrix;
 pub mod point;
+pub mod quaternion;
+pub mod rng;
+pub mod sampling;
 pub mod util;
 pub mod vector;
 
+pub use self::bounds::Bounds;
 pub use self::matrix::*;
 pub use self::point::Point;
+pub use self::quaternion::Quaternion;
+pub use self::rng::Rng;
 pub use self::vector::Vector;
 
 #[cfg(test)]
@@ -22,4 +29,12 @@ mod test {
 
         assert_eq!((point * transform).xyz, [13.0, 0.0, 7.0]);
     }
+
+    #[test]
+    fn subtract_points() {
+        let a = Point::new(3.0, 2.0, 1.0);
+        let b = Point::new(5.0, 6.0, 7.0);
+
+        assert_eq!(a - b, Vector::new(-2.0, -4.0, -6.0));
+    }
 }
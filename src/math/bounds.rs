@@ -0,0 +1,222 @@
+use super::{Matrix4x4, Point, Vector};
+
+// an axis-aligned bounding box
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    // a box containing nothing, so that any union with it is the other box
+    pub fn empty() -> Self {
+        Self {
+            min: Point::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn union(&self, other: Self) -> Self {
+        Self::new(
+            Point::new_arr(self.min.xyz.zip(other.min.xyz).map(|(a, b)| a.min(b))),
+            Point::new_arr(self.max.xyz.zip(other.max.xyz).map(|(a, b)| a.max(b))),
+        )
+    }
+
+    pub fn add_point(&self, point: Point) -> Self {
+        self.union(Self::new(point, point))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..3).any(|axis| self.min.xyz[axis] > self.max.xyz[axis])
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        (0..3).all(|axis| (self.min.xyz[axis]..=self.max.xyz[axis]).contains(&point.xyz[axis]))
+    }
+
+    pub fn contains_bounds(&self, other: Self) -> bool {
+        other.is_empty() || (self.contains_point(other.min) && self.contains_point(other.max))
+    }
+
+    // cuts the box in two with a plane perpendicular to `axis` at `at`, clamped to the box
+    pub fn split_at(&self, axis: usize, at: f32) -> (Self, Self) {
+        let at = at.clamp(self.min.xyz[axis], self.max.xyz[axis]);
+
+        let mut left = *self;
+        let mut right = *self;
+        left.max.xyz[axis] = at;
+        right.min.xyz[axis] = at;
+
+        (left, right)
+    }
+
+    // halves the box along its longest axis
+    pub fn split(&self) -> (Self, Self) {
+        let axis = self.longest_axis();
+        self.split_at(axis, self.centroid().xyz[axis])
+    }
+
+    // the box around all eight transformed corners, so rotated boxes still contain the shape
+    pub fn transform(&self, transform: Matrix4x4) -> Self {
+        let [min, max] = [self.min.xyz, self.max.xyz];
+
+        (0..8).fold(Self::empty(), |acc, i| {
+            let corner = Point::new(
+                if i & 1 == 0 { min[0] } else { max[0] },
+                if i & 2 == 0 { min[1] } else { max[1] },
+                if i & 4 == 0 { min[2] } else { max[2] },
+            );
+
+            acc.add_point(corner * transform)
+        })
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::new_arr(self.min.xyz.zip(self.max.xyz).map(|(a, b)| (a + b) * 0.5))
+    }
+
+    pub fn extent(&self) -> Vector {
+        self.max - self.min
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let [x, y, z] = self.extent().xyz.map(|v| v.max(0.0));
+        2.0 * (x * y + y * z + z * x)
+    }
+
+    // the index of the axis the box is longest along
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.extent().xyz;
+
+        (0..3).fold(0, |longest, axis| match extent[axis] > extent[longest] {
+            true => axis,
+            false => longest,
+        })
+    }
+
+    // the times the ray enters and leaves the box, if it passes through it in front of the origin
+    pub fn intersect(&self, origin: Point, direction: Vector) -> Option<(f32, f32)> {
+        let mut tmin = f32::NEG_INFINITY;
+        let mut tmax = f32::INFINITY;
+
+        for axis in 0..3 {
+            // dividing by zero gives infinities, which the slab test handles for free
+            let inverse = 1.0 / direction.xyz[axis];
+            let mut near = (self.min.xyz[axis] - origin.xyz[axis]) * inverse;
+            let mut far = (self.max.xyz[axis] - origin.xyz[axis]) * inverse;
+
+            if near > far {
+                std::mem::swap(&mut near, &mut far);
+            }
+
+            // f32::max and f32::min ignore the NaNs from rays lying exactly on a slab
+            tmin = tmin.max(near);
+            tmax = tmax.min(far);
+        }
+
+        match tmin <= tmax && tmax >= 0.0 {
+            true => Some((tmin, tmax)),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn union() {
+        let a = Bounds::new(Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 1.0, 1.0));
+        let b = Bounds::new(Point::new(0.0, -2.0, 0.5), Point::new(0.5, 0.5, 3.0));
+
+        assert_eq!(
+            a.union(b),
+            Bounds::new(Point::new(-1.0, -2.0, 0.0), Point::new(1.0, 1.0, 3.0))
+        );
+        assert_eq!(Bounds::empty().union(a), a);
+    }
+
+    #[test]
+    fn transform_rotated_box() {
+        let unit = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let bounds = unit.transform(Matrix4x4::identity().rotate_y(PI / 4.0));
+        let half_diagonal = 2.0_f32.sqrt();
+
+        assert_eq!(
+            bounds.min.round(0.001),
+            Point::new(-half_diagonal, -1.0, -half_diagonal).round(0.001)
+        );
+        assert_eq!(
+            bounds.max.round(0.001),
+            Point::new(half_diagonal, 1.0, half_diagonal).round(0.001)
+        );
+    }
+
+    #[test]
+    fn intersect() {
+        let bounds = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+
+        assert_eq!(
+            bounds.intersect(Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            Some((4.0, 6.0))
+        );
+        assert_eq!(
+            bounds.intersect(Point::new(2.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            None
+        );
+        // behind the origin
+        assert_eq!(
+            bounds.intersect(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn containment() {
+        let bounds = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let inner = Bounds::new(Point::new(-0.5, 0.0, 0.0), Point::new(1.0, 0.5, 0.5));
+        let outer = Bounds::new(Point::new(-0.5, 0.0, 0.0), Point::new(1.5, 0.5, 0.5));
+
+        assert!(bounds.contains_point(Point::new(1.0, 0.0, -1.0)));
+        assert!(!bounds.contains_point(Point::new(0.0, 1.1, 0.0)));
+        assert!(bounds.contains_bounds(inner));
+        assert!(!bounds.contains_bounds(outer));
+        assert!(bounds.contains_bounds(Bounds::empty()));
+        assert!(Bounds::empty().is_empty());
+    }
+
+    #[test]
+    fn split() {
+        let bounds = Bounds::new(Point::new(-1.0, -4.0, -5.0), Point::new(9.0, 6.0, 5.0));
+        let (left, right) = bounds.split();
+
+        assert_eq!(
+            left,
+            Bounds::new(Point::new(-1.0, -4.0, -5.0), Point::new(4.0, 6.0, 5.0))
+        );
+        assert_eq!(
+            right,
+            Bounds::new(Point::new(4.0, -4.0, -5.0), Point::new(9.0, 6.0, 5.0))
+        );
+
+        let (left, right) = bounds.split_at(1, 10.0);
+        assert_eq!(left, bounds);
+        assert_eq!(right.extent().xyz[1], 0.0);
+    }
+
+    #[test]
+    fn surface_area() {
+        let bounds = Bounds::new(Point::zero(), Point::new(1.0, 2.0, 3.0));
+
+        assert_eq!(bounds.surface_area(), 22.0);
+        assert_eq!(bounds.longest_axis(), 2);
+        assert_eq!(Bounds::empty().surface_area(), 0.0);
+    }
+}
@@ -1,5 +1,10 @@
 use unroll::unroll_for_loops;
 
+use super::{Point, Quaternion, Vector};
+
+// a translation, a rotation and a scale along each axis, as split out by `decompose`
+pub type Decomposition = (Vector, Quaternion, Vector);
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub struct Matrix4x4 {
     pub data: [[f32; 4]; 4],
@@ -81,6 +86,75 @@ impl Matrix4x4 {
         ]))
     }
 
+    // moves the world so an eye at `from` looks towards `to`, with `up` roughly upwards
+    pub fn view_transform(from: Point, to: Point, up: Vector) -> Self {
+        let forward = (to - from).normalize();
+        let left = forward.cross(up.normalize());
+        let true_up = left.cross(forward);
+        let [lx, ly, lz] = left.xyz;
+        let [ux, uy, uz] = true_up.xyz;
+        let [fx, fy, fz] = forward.xyz;
+
+        Self::new([
+            [lx, ly, lz, 0.0],
+            [ux, uy, uz, 0.0],
+            [-fx, -fy, -fz, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+        .translate(-from.xyz[0], -from.xyz[1], -from.xyz[2])
+    }
+
+    // splits the matrix into a translation, a rotation and a scale along each axis,
+    // shearing can't be represented so it is lost
+    pub fn decompose(&self) -> Decomposition {
+        let m = self.data;
+        let translation = Vector::new(m[0][3], m[1][3], m[2][3]);
+
+        // each column is an axis rotated and then stretched by its scale
+        let mut scale = [0, 1, 2].map(|j| Vector::new(m[0][j], m[1][j], m[2][j]).magnitude());
+
+        // a mirrored transform keeps its handedness in a negative scale
+        if self.determinant() < 0.0 {
+            scale[0] = -scale[0];
+        }
+
+        // dropping the translation and dividing the scale back out leaves the rotation
+        let mut linear = *self;
+        for row in &mut linear.data[..3] {
+            row[3] = 0.0;
+        }
+        let rotation = linear.scale(1.0 / scale[0], 1.0 / scale[1], 1.0 / scale[2]);
+
+        (
+            translation,
+            Quaternion::from_matrix(&rotation),
+            Vector::new_arr(scale),
+        )
+    }
+
+    // the inverse of `decompose`, translating after rotating after scaling
+    pub fn compose(translation: Vector, rotation: Quaternion, scale: Vector) -> Self {
+        let [tx, ty, tz] = translation.xyz;
+        let [sx, sy, sz] = scale.xyz;
+
+        Self::identity()
+            .translate(tx, ty, tz)
+            .mul(rotation.to_matrix())
+            .scale(sx, sy, sz)
+    }
+
+    // blends the decomposed parts of both matrices, so rotations turn rather than squash
+    pub fn interpolate(&self, rhs: &Self, t: f32) -> Self {
+        Self::blend(self.decompose(), rhs.decompose(), t)
+    }
+
+    // `interpolate` for matrices that were already decomposed
+    pub fn blend(start: Decomposition, end: Decomposition, t: f32) -> Self {
+        let ((t1, r1, s1), (t2, r2, s2)) = (start, end);
+
+        Self::compose(t1 + (t2 - t1) * t, r1.slerp(r2, t), s1 + (s2 - s1) * t)
+    }
+
     #[unroll_for_loops]
     pub fn transpose(&self) -> Self {
         let mut result = Self::zero();
@@ -260,6 +334,7 @@ impl Matrix2x2 {
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::f32::consts::PI;
 
     #[test]
     fn test_matrix4x4_determinant() {
@@ -337,4 +412,58 @@ mod test {
             ])
         );
     }
+
+    #[test]
+    fn test_matrix4x4_view_transform() {
+        let m = Matrix4x4::view_transform(
+            Point::new(1.0, 3.0, 2.0),
+            Point::new(4.0, -2.0, 8.0),
+            Vector::new(1.0, 1.0, 0.0),
+        );
+        assert_eq!(
+            m.round(10000.0),
+            Matrix4x4::new([
+                [-0.50709, 0.50709, 0.67612, -2.36643],
+                [0.76772, 0.60609, 0.12122, -2.82843],
+                [-0.35857, 0.59761, -0.71714, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])
+            .round(10000.0)
+        );
+    }
+
+    #[test]
+    fn test_matrix4x4_decompose() {
+        let m = Matrix4x4::identity()
+            .translate(1.0, -2.0, 3.0)
+            .rotate_y(PI / 3.0)
+            .scale(2.0, 0.5, 1.0);
+        let (translation, rotation, scale) = m.decompose();
+
+        assert_eq!(translation, Vector::new(1.0, -2.0, 3.0));
+        assert_eq!(scale.round(0.0001), Vector::new(2.0, 0.5, 1.0));
+        assert_eq!(
+            Matrix4x4::compose(translation, rotation, scale).round(10000.0),
+            m.round(10000.0)
+        );
+    }
+
+    #[test]
+    fn test_matrix4x4_interpolate() {
+        let start = Matrix4x4::identity().translate(0.0, 0.0, 0.0);
+        let end = Matrix4x4::identity()
+            .translate(4.0, 0.0, 0.0)
+            .rotate_z(PI / 2.0)
+            .scale(3.0, 3.0, 3.0);
+
+        assert_eq!(
+            start.interpolate(&end, 0.5).round(10000.0),
+            Matrix4x4::identity()
+                .translate(2.0, 0.0, 0.0)
+                .rotate_z(PI / 4.0)
+                .scale(2.0, 2.0, 2.0)
+                .round(10000.0)
+        );
+        assert_eq!(start.interpolate(&end, 0.0).round(10000.0), start);
+    }
 }
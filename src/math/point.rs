@@ -36,13 +36,13 @@ impl Sub<Self> for Point {
     fn sub(self, rhs: Self) -> Vector {
         Vector::new(
             self.xyz[0] - rhs.xyz[0],
-            self.xyz[1] - rhs.xyz[0],
-            self.xyz[2] - rhs.xyz[0],
+            self.xyz[1] - rhs.xyz[1],
+            self.xyz[2] - rhs.xyz[2],
         )
     }
 }
 
-// adding a vector to a point results in a new point
+// subtracting a vector from a point results in a new point
 impl Sub<Vector> for Point {
     type Output = Self;
     fn sub(self, rhs: Vector) -> Self {
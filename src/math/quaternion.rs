@@ -0,0 +1,172 @@
+use super::Matrix4x4;
+
+// a rotation, used to interpolate between orientations without the shearing
+// that blending rotation matrices directly would give
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Quaternion {
+    pub w: f32,
+    pub xyz: [f32; 3],
+}
+
+impl Quaternion {
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Self { w, xyz: [x, y, z] }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    // the rotation in the upper 3x3 of a matrix, which has to be orthonormal
+    pub fn from_matrix(m: &Matrix4x4) -> Self {
+        let m = m.data;
+        let trace = m[0][0] + m[1][1] + m[2][2];
+
+        // pick the largest of the four components to divide by, so it can't be near zero
+        let quaternion = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(
+                0.25 * s,
+                (m[2][1] - m[1][2]) / s,
+                (m[0][2] - m[2][0]) / s,
+                (m[1][0] - m[0][1]) / s,
+            )
+        } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+            let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[2][1] - m[1][2]) / s,
+                0.25 * s,
+                (m[0][1] + m[1][0]) / s,
+                (m[0][2] + m[2][0]) / s,
+            )
+        } else if m[1][1] > m[2][2] {
+            let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+            Self::new(
+                (m[0][2] - m[2][0]) / s,
+                (m[0][1] + m[1][0]) / s,
+                0.25 * s,
+                (m[1][2] + m[2][1]) / s,
+            )
+        } else {
+            let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+            Self::new(
+                (m[1][0] - m[0][1]) / s,
+                (m[0][2] + m[2][0]) / s,
+                (m[1][2] + m[2][1]) / s,
+                0.25 * s,
+            )
+        };
+
+        quaternion.normalize()
+    }
+
+    pub fn to_matrix(self) -> Matrix4x4 {
+        let w = self.w;
+        let [x, y, z] = self.xyz;
+
+        Matrix4x4::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - z * w),
+                2.0 * (x * z + y * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * y + z * w),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - x * w),
+                0.0,
+            ],
+            [
+                2.0 * (x * z - y * w),
+                2.0 * (y * z + x * w),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn dot(&self, rhs: Self) -> f32 {
+        self.w * rhs.w + (0..3).map(|i| self.xyz[i] * rhs.xyz[i]).sum::<f32>()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let magnitude = self.dot(*self).sqrt();
+        Self {
+            w: self.w / magnitude,
+            xyz: self.xyz.map(|v| v / magnitude),
+        }
+    }
+
+    // spherical interpolation, turning at a constant speed along the shortest arc
+    pub fn slerp(&self, rhs: Self, t: f32) -> Self {
+        let mut cos = self.dot(rhs);
+
+        // q and -q are the same rotation, flip one so they're on the same side
+        let rhs = match cos < 0.0 {
+            true => {
+                cos = -cos;
+                Self {
+                    w: -rhs.w,
+                    xyz: rhs.xyz.map(|v| -v),
+                }
+            }
+            false => rhs,
+        };
+
+        // nearly the same rotation, where sin(theta) is too small to divide by
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let theta = cos.acos();
+            let sin = theta.sin();
+            (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+        };
+
+        Self {
+            w: self.w * a + rhs.w * b,
+            xyz: [0, 1, 2].map(|i| self.xyz[i] * a + rhs.xyz[i] * b),
+        }
+        .normalize()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn matrix_round_trip() {
+        for rotation in [
+            Matrix4x4::identity(),
+            Matrix4x4::identity().rotate_x(PI / 3.0),
+            Matrix4x4::identity().rotate_y(PI),
+            Matrix4x4::identity()
+                .rotate_z(-PI / 2.0)
+                .rotate_x(0.3)
+                .rotate_y(2.5),
+        ] {
+            assert_eq!(
+                Quaternion::from_matrix(&rotation).to_matrix().round(1000.0),
+                rotation.round(1000.0)
+            );
+        }
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let start = Quaternion::identity();
+        let end = Quaternion::from_matrix(&Matrix4x4::identity().rotate_z(PI / 2.0));
+
+        assert_eq!(
+            start.slerp(end, 0.5).to_matrix().round(1000.0),
+            Matrix4x4::identity().rotate_z(PI / 4.0).round(1000.0)
+        );
+        assert_eq!(
+            start.slerp(end, 1.0).to_matrix().round(1000.0),
+            end.to_matrix().round(1000.0)
+        );
+    }
+}
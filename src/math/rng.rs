@@ -0,0 +1,81 @@
+// small xorshift64* generator, plenty for jittering samples and cheap to seed
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // scramble the seed with splitmix64 so nearby seeds give unrelated streams
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+
+        // xorshift gets stuck at zero
+        Self {
+            state: if z == 0 { 0x9e37_79b9_7f4a_7c15 } else { z },
+        }
+    }
+
+    // the generator for one pixel of a render, which only depends on the pixel and the
+    // render's seed, so renders come out the same however they're split between threads
+    pub fn for_pixel(seed: u64, x: i32, y: i32) -> Self {
+        let pixel = ((x as u32 as u64) << 32) | y as u32 as u64;
+        Self::new(Self::new(seed).state ^ pixel)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+
+        (x.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 32) as u32
+    }
+
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        let stream = |mut rng: Rng| (0..8).map(|_| rng.next_u32()).collect::<Vec<_>>();
+
+        assert_eq!(stream(Rng::new(42)), stream(Rng::new(42)));
+        assert_ne!(stream(Rng::new(42)), stream(Rng::new(43)));
+        assert_eq!(
+            stream(Rng::for_pixel(1, 3, 4)),
+            stream(Rng::for_pixel(1, 3, 4))
+        );
+        assert_ne!(
+            stream(Rng::for_pixel(1, 3, 4)),
+            stream(Rng::for_pixel(1, 4, 3))
+        );
+        assert_ne!(
+            stream(Rng::for_pixel(1, 3, 4)),
+            stream(Rng::for_pixel(2, 3, 4))
+        );
+    }
+
+    #[test]
+    fn floats_are_uniform() {
+        let mut rng = Rng::new(0);
+        let mut buckets = [0; 10];
+
+        for _ in 0..10000 {
+            let x = rng.next_f32();
+            assert!((0.0..1.0).contains(&x));
+            buckets[(x * 10.0) as usize] += 1;
+        }
+
+        assert!(buckets.iter().all(|count| (900..1100).contains(count)));
+    }
+}
@@ -0,0 +1,104 @@
+use std::f32::consts::PI;
+
+use super::{Rng, Vector};
+
+// the direction at `cos` to `axis` and turned `phi` around it, the axis has to be normalized
+pub fn around(axis: Vector, cos: f32, phi: f32) -> Vector {
+    let sin = (1.0 - cos * cos).max(0.0).sqrt();
+    let (tangent, bitangent) = axis.perpendiculars();
+
+    tangent * (sin * phi.cos()) + bitangent * (sin * phi.sin()) + axis * cos
+}
+
+// a uniformly distributed point in the unit disk, taking the root keeps the center from clumping
+pub fn unit_disk(rng: &mut Rng) -> (f32, f32) {
+    let radius = rng.next_f32().sqrt();
+    let theta = 2.0 * PI * rng.next_f32();
+
+    (radius * theta.cos(), radius * theta.sin())
+}
+
+// a uniformly distributed direction, which is a point on the unit sphere
+pub fn unit_sphere(rng: &mut Rng) -> Vector {
+    let z = 1.0 - 2.0 * rng.next_f32();
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * PI * rng.next_f32();
+
+    Vector::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+// a uniformly distributed direction on the side of the normal, with a density of 1 / 2pi
+pub fn uniform_hemisphere(normal: Vector, rng: &mut Rng) -> Vector {
+    let cos = rng.next_f32();
+    around(normal, cos, 2.0 * PI * rng.next_f32())
+}
+
+// a direction around the normal, with a density proportional to the cosine of its angle to it
+pub fn cosine_hemisphere(normal: Vector, rng: &mut Rng) -> Vector {
+    // points in the disk projected up onto the hemisphere
+    let (x, y) = unit_disk(rng);
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+    let (tangent, bitangent) = normal.perpendiculars();
+
+    (tangent * x + bitangent * y + normal * z).normalize()
+}
+
+// a direction with a density proportional to cos^exponent of its angle to the axis
+pub fn power_cosine(axis: Vector, exponent: f32, rng: &mut Rng) -> Vector {
+    let cos = rng.next_f32().powf(1.0 / (exponent + 1.0));
+    around(axis, cos, 2.0 * PI * rng.next_f32())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::util::about_equals;
+
+    fn mean_cos(sample: impl Fn(&mut Rng) -> Vector, normal: Vector) -> f32 {
+        let mut rng = Rng::new(1);
+
+        (0..20000)
+            .map(|_| {
+                let direction = sample(&mut rng);
+                assert!(about_equals(direction.magnitude(), 1.0, 0.001));
+                direction.dot(normal)
+            })
+            .sum::<f32>()
+            / 20000.0
+    }
+
+    #[test]
+    fn disk() {
+        let mut rng = Rng::new(0);
+        let points: Vec<_> = (0..1000).map(|_| unit_disk(&mut rng)).collect();
+
+        assert!(points.iter().all(|(x, y)| x * x + y * y <= 1.0));
+        // as many points in the inner half of the area as in the outer
+        let inner = points.iter().filter(|(x, y)| x * x + y * y < 0.5).count();
+        assert!((450..550).contains(&inner), "{}", inner);
+    }
+
+    #[test]
+    fn directions() {
+        let normal = Vector::new(0.0, 0.6, 0.8);
+
+        // the average cosine to the normal is 0 over the sphere, 1/2 over the hemisphere
+        // and 2/3 when weighted by the cosine
+        assert!(about_equals(mean_cos(unit_sphere, normal), 0.0, 0.02));
+        assert!(about_equals(
+            mean_cos(|rng| uniform_hemisphere(normal, rng), normal),
+            0.5,
+            0.02
+        ));
+        assert!(about_equals(
+            mean_cos(|rng| cosine_hemisphere(normal, rng), normal),
+            2.0 / 3.0,
+            0.02
+        ));
+        assert!(about_equals(
+            mean_cos(|rng| power_cosine(normal, 1.0, rng), normal),
+            2.0 / 3.0,
+            0.02
+        ));
+    }
+}
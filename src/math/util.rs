@@ -5,3 +5,6 @@ pub fn about_equals(a: f32, b: f32, epsilon: f32) -> bool {
         false
     }
 }
+
+// offset used to nudge points off of surfaces so they don't intersect themselves
+pub const EPSILON: f32 = 0.001;
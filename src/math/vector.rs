@@ -68,6 +68,18 @@ impl Vector {
     pub fn reflect(&self, normal: Self) -> Self {
         *self - normal * 2.0 * self.dot(normal)
     }
+
+    // two unit vectors perpendicular to this one and each other, the vector has to be normalized
+    pub fn perpendiculars(&self) -> (Self, Self) {
+        // any axis the vector isn't close to will do
+        let helper = match self.xyz[0].abs() > 0.9 {
+            true => Self::new(0.0, 1.0, 0.0),
+            false => Self::new(1.0, 0.0, 0.0),
+        };
+        let tangent = self.cross(helper).normalize();
+
+        (tangent, self.cross(tangent))
+    }
 }
 
 // adding two vectors results in a new vector
@@ -1,46 +1,170 @@
+pub mod antialiasing;
+pub mod area_light;
+pub mod attenuation;
+pub mod bsdf;
+pub mod bvh;
+pub mod camera;
 pub mod canvas;
 pub mod color;
+pub mod directional_light;
+pub mod environment;
+pub mod integrator;
 pub mod intersection;
+pub mod light;
 pub mod material;
+pub mod microfacet;
+pub mod obj;
 pub mod object;
+pub mod object_light;
+pub mod pattern;
 pub mod point_light;
 pub mod ray;
+pub mod renderer;
+pub mod spot_light;
+pub mod triangle;
 pub mod world;
 
 pub use canvas::Canvas;
 pub use color::Color;
+pub use light::{Light, LightSample};
 pub use material::Material;
 pub use object::Object;
+pub use pattern::Pattern;
 pub use point_light::PointLight;
 pub use ray::Ray;
 
-use crate::math::{Point, Vector};
+use crate::math::Vector;
 
+use bsdf::Bsdf;
+
+// `color` is the surface's color at the shaded point, its material's color or pattern
 pub fn lighting(
-    material: Material,
-    light: PointLight,
-    position: Point,
+    material: &Material,
+    color: Color,
+    light: &dyn Light,
     eye: Vector,
     normal: Vector,
+    // where the light was sampled from and how much of it reaches the point
+    samples: &[LightSample],
 ) -> Color {
-    let effective_color = material.color * light.intensity;
-    let light_vector = (light.position - position).normalize();
-    let ambient = effective_color * material.ambient;
-    let light_dot_normal = light_vector.dot(normal);
+    let ambient = color * light.intensity() * material.ambient;
+
+    if material.microfacet.is_some() {
+        // the brdf is normalized, which makes lights dimmer than under the phong terms
+        let bsdf = material.surface_bsdf(color);
+        let reflected = samples
+            .iter()
+            .fold(Color::new(0.0, 0.0, 0.0), |acc, sample| {
+                let cos = sample.direction.dot(normal).max(0.0);
+                let brdf = bsdf.eval(eye, sample.direction, normal);
+
+                acc + sample.intensity * sample.transmittance * brdf * cos
+            });
+
+        return ambient + reflected * (1.0 / samples.len().max(1) as f32);
+    }
 
     let mut diffuse = Color::new(0.0, 0.0, 0.0);
     let mut specular = Color::new(0.0, 0.0, 0.0);
-    if light_dot_normal >= 0.0 {
-        diffuse = effective_color * material.diffuse * light_dot_normal;
+    for sample in samples {
+        let light_vector = sample.direction;
+        let light_dot_normal = light_vector.dot(normal);
 
-        let reflect = light_vector.reflect(normal);
-        let reflect_dot_eye = reflect.dot(eye);
+        if light_dot_normal >= 0.0 {
+            let incoming = sample.intensity * sample.transmittance;
+            diffuse = diffuse + color * incoming * material.diffuse * light_dot_normal;
 
-        if reflect_dot_eye > 0.0 {
-            let factor = reflect_dot_eye.powf(material.shininess);
-            specular = light.intensity * material.specular * factor;
+            let reflect = light_vector.negate().reflect(normal);
+            let reflect_dot_eye = reflect.dot(eye);
+
+            if reflect_dot_eye > 0.0 {
+                let factor = reflect_dot_eye.powf(material.shininess);
+                specular = specular + incoming * material.specular * factor;
+            }
         }
     }
 
-    ambient + diffuse + specular
+    // average over the samples so area lights aren't brighter than point lights
+    ambient + (diffuse + specular) * (1.0 / samples.len().max(1) as f32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{Matrix4x4, Point};
+    use attenuation::Attenuation;
+
+    #[test]
+    fn lighting_eye_in_reflection() {
+        let sphere = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let samples = [LightSample::towards(
+            Point::zero(),
+            Point::new(0.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+            Attenuation::None,
+        )];
+        let eye = Vector::new(0.0, -(2.0_f32.sqrt()) / 2.0, -(2.0_f32.sqrt()) / 2.0);
+        let normal = Vector::new(0.0, 0.0, -1.0);
+
+        let result = lighting(
+            &sphere.material,
+            sphere.color_at(Point::zero(), 0.0),
+            &light,
+            eye,
+            normal,
+            &samples,
+        );
+
+        assert_eq!(
+            result.round(0.0001),
+            Color::new(1.6364, 1.6364, 1.6364).round(0.0001)
+        );
+    }
+
+    #[test]
+    fn lighting_microfacet_highlight() {
+        let material = Material::new_microfacet(Color::new(1.0, 0.8, 0.6), 1.0, 0.3);
+        let color = material.color;
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let samples = [LightSample::towards(
+            Point::zero(),
+            Point::new(0.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+            Attenuation::None,
+        )];
+        let normal = Vector::new(0.0, 0.0, -1.0);
+        let shade = |eye: Vector| lighting(&material, color, &light, eye, normal, &samples);
+
+        let in_reflection = shade(Vector::new(
+            0.0,
+            -(2.0_f32.sqrt()) / 2.0,
+            -(2.0_f32.sqrt()) / 2.0,
+        ));
+        let head_on = shade(Vector::new(0.0, 0.0, -1.0));
+        let ambient = color * 0.1;
+
+        assert!(in_reflection.rgb[0] > head_on.rgb[0] && head_on.rgb[0] > ambient.rgb[0]);
+        // metals reflect in their own color
+        assert!(in_reflection.rgb[0] > in_reflection.rgb[2]);
+
+        // lit from behind only the ambient term is left
+        let behind = [LightSample::towards(
+            Point::zero(),
+            Point::new(0.0, 0.0, 10.0),
+            Color::new(1.0, 1.0, 1.0),
+            Attenuation::None,
+        )];
+        assert_eq!(
+            lighting(
+                &material,
+                color,
+                &light,
+                Vector::new(0.0, 0.0, -1.0),
+                normal,
+                &behind
+            ),
+            ambient
+        );
+    }
 }
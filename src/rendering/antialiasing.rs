@@ -0,0 +1,253 @@
+use crate::math::Rng;
+
+// where in a pixel the samples are taken, as offsets in [0, 1)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Sampler {
+    // evenly spaced, the count is rounded up to a square. later batches shift the grid
+    // within its cells so they don't repeat the first
+    Grid,
+    // one random point per grid cell, the count is rounded up to a square
+    Jittered,
+    // the halton sequence in bases 2 and 3
+    Halton,
+    // the first two dimensions of the sobol sequence
+    Sobol,
+}
+
+impl Sampler {
+    // the offsets of samples `first..first + count`, where `scramble` decorrelates the
+    // low-discrepancy sequences between pixels while keeping them even within a pixel
+    pub fn points(&self, first: u32, count: u32, scramble: u32, rng: &mut Rng) -> Vec<(f32, f32)> {
+        match self {
+            Self::Grid | Self::Jittered => {
+                let side = (count as f32).sqrt().ceil().max(1.0) as u32;
+                // the first batch sits in the middle of the cells, the rest move around them
+                // along the halton sequence
+                let batch = first / (side * side);
+                let shift = [
+                    (0.5 + radical_inverse(batch, 2)).fract(),
+                    (0.5 + radical_inverse(batch, 3)).fract(),
+                ];
+
+                (0..side * side)
+                    .map(|index| {
+                        let (x, y) = ((index % side) as f32, (index / side) as f32);
+                        let (dx, dy) = match self {
+                            Self::Jittered => (rng.next_f32(), rng.next_f32()),
+                            _ => (shift[0], shift[1]),
+                        };
+
+                        ((x + dx) / side as f32, (y + dy) / side as f32)
+                    })
+                    .collect()
+            }
+            Self::Halton => {
+                // a random rotation of the sequence, wrapped back into [0, 1)
+                let offset = [scramble & 0xffff, scramble >> 16].map(|x| x as f32 / 65536.0);
+
+                (first..first + count)
+                    .map(|index| {
+                        let x = (radical_inverse(index + 1, 2) + offset[0]).fract();
+                        let y = (radical_inverse(index + 1, 3) + offset[1]).fract();
+                        (x, y)
+                    })
+                    .collect()
+            }
+            Self::Sobol => {
+                // flipping the same bits of every point keeps it a (0, 2)-sequence
+                let masks = [scramble, scramble.rotate_left(16)];
+
+                (first..first + count)
+                    .map(|index| {
+                        let x = index.reverse_bits() ^ masks[0];
+                        let y = sobol_second(index) ^ masks[1];
+                        (to_unit(x), to_unit(y))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// mirrors the digits of `index` in `base` around the decimal point
+fn radical_inverse(mut index: u32, base: u32) -> f32 {
+    let inverse_base = 1.0 / base as f32;
+    let mut factor = inverse_base;
+    let mut result = 0.0;
+
+    while index > 0 {
+        result += (index % base) as f32 * factor;
+        index /= base;
+        factor *= inverse_base;
+    }
+
+    result
+}
+
+// the second sobol dimension, whose direction numbers are the rows of pascal's triangle mod 2
+fn sobol_second(mut index: u32) -> u32 {
+    let mut direction = 1 << 31;
+    let mut result = 0;
+
+    while index != 0 {
+        if index & 1 == 1 {
+            result ^= direction;
+        }
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+
+    result
+}
+
+// the top 24 bits as a float, so the result can't round up to 1
+fn to_unit(bits: u32) -> f32 {
+    (bits >> 8) as f32 / (1 << 24) as f32
+}
+
+// how much a sample contributes to a pixel depending on how far from its center it is
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PixelFilter {
+    Box,
+    Tent,
+    Gaussian,
+    // mitchell-netravali with b = c = 1/3, which sharpens slightly with a negative lobe
+    Mitchell,
+}
+
+impl PixelFilter {
+    // how far from the pixel's center samples are taken, in pixels
+    pub fn radius(&self) -> f32 {
+        match self {
+            Self::Box => 0.5,
+            Self::Tent => 1.0,
+            Self::Gaussian => 1.5,
+            Self::Mitchell => 2.0,
+        }
+    }
+
+    // the filters are separable, so this is the product of the weights along each axis
+    pub fn weight(&self, x: f32, y: f32) -> f32 {
+        self.weight_1d(x) * self.weight_1d(y)
+    }
+
+    fn weight_1d(&self, x: f32) -> f32 {
+        let radius = self.radius();
+        let x = x.abs();
+
+        if x > radius {
+            return 0.0;
+        }
+
+        match self {
+            Self::Box => 1.0,
+            Self::Tent => radius - x,
+            Self::Gaussian => {
+                // shifted down so the weight reaches zero at the radius instead of cutting off
+                let alpha = 2.0;
+                ((-alpha * x * x).exp() - (-alpha * radius * radius).exp()).max(0.0)
+            }
+            Self::Mitchell => {
+                let (b, c) = (1.0 / 3.0, 1.0 / 3.0);
+                let x = 2.0 * x / radius;
+
+                let weight = if x < 1.0 {
+                    (12.0 - 9.0 * b - 6.0 * c) * x.powi(3)
+                        + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2)
+                        + (6.0 - 2.0 * b)
+                } else {
+                    (-b - 6.0 * c) * x.powi(3)
+                        + (6.0 * b + 30.0 * c) * x.powi(2)
+                        + (-12.0 * b - 48.0 * c) * x
+                        + (8.0 * b + 24.0 * c)
+                };
+
+                weight / 6.0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn quadrants(points: &[(f32, f32)]) -> [u32; 4] {
+        let mut counts = [0; 4];
+        for (x, y) in points {
+            counts[(*x >= 0.5) as usize + 2 * (*y >= 0.5) as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn grid_points() {
+        let mut rng = Rng::new(0);
+
+        assert_eq!(
+            Sampler::Grid.points(0, 4, 0, &mut rng),
+            [(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+        // rounded up to the next square
+        assert_eq!(Sampler::Jittered.points(0, 5, 0, &mut rng).len(), 9);
+    }
+
+    #[test]
+    fn later_batches_take_new_points() {
+        let mut rng = Rng::new(0);
+
+        for sampler in [Sampler::Grid, Sampler::Jittered] {
+            let first = sampler.points(0, 4, 0, &mut rng);
+            let second = sampler.points(4, 4, 0, &mut rng);
+
+            assert!(first.iter().all(|point| !second.contains(point)));
+            assert_eq!(quadrants(&second), [1, 1, 1, 1]);
+        }
+    }
+
+    #[test]
+    fn low_discrepancy_points() {
+        let mut rng = Rng::new(0);
+
+        assert_eq!(
+            Sampler::Halton.points(0, 2, 0, &mut rng),
+            [(0.5, 1.0 / 3.0), (0.25, 2.0 / 3.0)]
+        );
+        assert_eq!(
+            Sampler::Sobol.points(0, 4, 0, &mut rng),
+            [(0.0, 0.0), (0.5, 0.5), (0.25, 0.75), (0.75, 0.25)]
+        );
+
+        // every sampler stays stratified across the quadrants when scrambled
+        for sampler in [Sampler::Jittered, Sampler::Halton, Sampler::Sobol] {
+            for scramble in [0, 0x1234_5678, u32::MAX] {
+                let points = sampler.points(0, 4, scramble, &mut rng);
+
+                assert!(points
+                    .iter()
+                    .all(|(x, y)| (0.0..1.0).contains(x) && (0.0..1.0).contains(y)));
+                if sampler != Sampler::Halton {
+                    assert_eq!(quadrants(&points), [1, 1, 1, 1]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn filter_weights() {
+        for filter in [
+            PixelFilter::Box,
+            PixelFilter::Tent,
+            PixelFilter::Gaussian,
+            PixelFilter::Mitchell,
+        ] {
+            assert!(filter.weight(0.0, 0.0) > 0.0);
+            assert_eq!(filter.weight(filter.radius() + 0.1, 0.0), 0.0);
+            assert!(filter.weight(0.0, 0.0) >= filter.weight(0.25, 0.25));
+        }
+
+        assert_eq!(PixelFilter::Tent.weight(0.5, 0.0), 0.5);
+        // the negative lobe
+        assert!(PixelFilter::Mitchell.weight(1.5, 0.0) < 0.0);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::math::{Point, Rng, Vector};
+
+use super::{attenuation::Attenuation, world::World, Color, Light, LightSample};
+
+// a rectangular light split into usteps * vsteps cells, each sampled once
+#[derive(Copy, Clone)]
+pub struct AreaLight {
+    pub corner: Point,
+    pub uvec: Vector,
+    pub usteps: u32,
+    pub vvec: Vector,
+    pub vsteps: u32,
+    pub intensity: Color,
+    pub position: Point,
+    pub attenuation: Attenuation,
+}
+
+impl AreaLight {
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: u32,
+        full_vvec: Vector,
+        vsteps: u32,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            corner,
+            uvec: full_uvec / usteps as f32,
+            usteps,
+            vvec: full_vvec / vsteps as f32,
+            vsteps,
+            intensity,
+            position: (full_uvec + full_vvec) / 2.0 + corner,
+            attenuation: Attenuation::None,
+        }
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.usteps * self.vsteps
+    }
+
+    // a random point inside the cell at (u, v)
+    pub fn point_on_light(&self, u: u32, v: u32, rng: &mut Rng) -> Point {
+        self.uvec * (u as f32 + rng.next_f32())
+            + self.vvec * (v as f32 + rng.next_f32())
+            + self.corner
+    }
+
+    pub fn sample_positions(&self, rng: &mut Rng) -> Vec<Point> {
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| self.point_on_light(u, v, rng))
+            .collect()
+    }
+}
+
+impl Light for AreaLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, _world: &World, point: Point, _time: f32, rng: &mut Rng) -> Vec<LightSample> {
+        self.sample_positions(rng)
+            .into_iter()
+            .map(|position| LightSample::towards(point, position, self.intensity, self.attenuation))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_area_light() {
+        let light = AreaLight::new(
+            Point::zero(),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            Color::new(1.0, 1.0, 1.0),
+        );
+
+        assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+        assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+        assert_eq!(light.sample_count(), 8);
+        assert_eq!(light.position, Point::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn jittered_points_stay_in_their_cells() {
+        let light = AreaLight::new(
+            Point::zero(),
+            Vector::new(2.0, 0.0, 0.0),
+            4,
+            Vector::new(0.0, 0.0, 1.0),
+            2,
+            Color::new(1.0, 1.0, 1.0),
+        );
+        let mut rng = Rng::new(7);
+
+        for (i, point) in light.sample_positions(&mut rng).iter().enumerate() {
+            let (u, v) = ((i % 4) as f32, (i / 4) as f32);
+
+            assert!(point.xyz[0] >= u * 0.5 && point.xyz[0] < (u + 1.0) * 0.5);
+            assert!(point.xyz[2] >= v * 0.5 && point.xyz[2] < (v + 1.0) * 0.5);
+            assert_eq!(point.xyz[1], 0.0);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// how a light's intensity falls off with distance. with `InverseSquare` the
+// intensity of a light is what it delivers at a distance of 1
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Attenuation {
+    None,
+    Linear,
+    InverseSquare,
+    Coefficients {
+        constant: f32,
+        linear: f32,
+        quadratic: f32,
+    },
+}
+
+impl Attenuation {
+    pub fn factor(&self, distance: f32) -> f32 {
+        let (constant, linear, quadratic) = match *self {
+            Self::None => return 1.0,
+            Self::Linear => (0.0, 1.0, 0.0),
+            Self::InverseSquare => (0.0, 0.0, 1.0),
+            Self::Coefficients {
+                constant,
+                linear,
+                quadratic,
+            } => (constant, linear, quadratic),
+        };
+
+        1.0 / (constant + linear * distance + quadratic * distance * distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn factor() {
+        let coefficients = Attenuation::Coefficients {
+            constant: 1.0,
+            linear: 0.5,
+            quadratic: 0.25,
+        };
+
+        assert_eq!(Attenuation::None.factor(10.0), 1.0);
+        assert_eq!(Attenuation::Linear.factor(4.0), 0.25);
+        assert_eq!(Attenuation::InverseSquare.factor(4.0), 0.0625);
+        // closer than a unit away lights brighten past their intensity
+        assert_eq!(Attenuation::InverseSquare.factor(0.5), 4.0);
+        assert_eq!(coefficients.factor(2.0), 1.0 / 3.0);
+
+        let boosted = Attenuation::Coefficients {
+            constant: 0.5,
+            linear: 0.0,
+            quadratic: 0.0,
+        };
+        assert_eq!(boosted.factor(10.0), 2.0);
+    }
+}
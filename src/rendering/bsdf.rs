@@ -0,0 +1,439 @@
+use std::f32::consts::PI;
+
+use crate::math::{
+    sampling::{cosine_hemisphere, power_cosine},
+    Rng, Vector,
+};
+
+use super::{microfacet::Microfacet, Color};
+
+// how a surface scatters light, new surface models only need to say how they are
+// evaluated and sampled and the path tracer picks them up as is. `wo` points towards
+// the eye, `wi` towards where the light comes from, and both point away from the surface
+pub trait Bsdf {
+    // the light scattered towards `wo` per unit of light arriving from `wi`, not counting
+    // the cosine at the light's side. zero for surfaces that only scatter in single directions
+    fn eval(&self, wo: Vector, wi: Vector, normal: Vector) -> Color;
+
+    // a direction for light to arrive from, or none if the light was absorbed
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample>;
+
+    // the density per unit solid angle with which `sample` picks `wi`
+    fn pdf(&self, wo: Vector, wi: Vector, normal: Vector) -> f32;
+
+    // mirrors and glass, which light sampling can't find the directions of
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct BsdfSample {
+    pub direction: Vector,
+    // what the light from `direction` is multiplied by, the brdf times the cosine over the pdf
+    pub weight: Color,
+    // the density the direction was picked with, zero for specular surfaces
+    pub pdf: f32,
+}
+
+impl BsdfSample {
+    // a sample of a surface that isn't specular, weighted by its own brdf and pdf
+    pub fn new(bsdf: &dyn Bsdf, wo: Vector, wi: Vector, normal: Vector) -> Option<Self> {
+        let pdf = bsdf.pdf(wo, wi, normal);
+        let cos = wi.dot(normal);
+
+        // the lobe can stick out below the surface, where nothing is reflected
+        if pdf <= 0.0 || cos <= 0.0 {
+            return None;
+        }
+
+        Some(Self {
+            direction: wi,
+            weight: bsdf.eval(wo, wi, normal) * (cos / pdf),
+            pdf,
+        })
+    }
+}
+
+// a perfectly matte surface, reflecting the same light in every direction
+#[derive(Copy, Clone, Debug)]
+pub struct Lambertian {
+    pub albedo: Color,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Bsdf for Lambertian {
+    fn eval(&self, wo: Vector, wi: Vector, normal: Vector) -> Color {
+        match wo.dot(normal) > 0.0 && wi.dot(normal) > 0.0 {
+            true => self.albedo * (1.0 / PI),
+            false => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample> {
+        BsdfSample::new(self, wo, cosine_hemisphere(normal, rng), normal)
+    }
+
+    fn pdf(&self, wo: Vector, wi: Vector, normal: Vector) -> f32 {
+        match wo.dot(normal) > 0.0 {
+            true => wi.dot(normal).max(0.0) / PI,
+            false => 0.0,
+        }
+    }
+}
+
+// the material's diffuse and specular terms, with the highlight normalized so
+// that it doesn't get brighter overall as it gets tighter
+#[derive(Copy, Clone, Debug)]
+pub struct Phong {
+    pub diffuse: Color,
+    pub specular: f32,
+    pub shininess: f32,
+}
+
+impl Phong {
+    // both lobes are scaled down when together they would reflect more light than arrives
+    pub fn new(diffuse: Color, specular: f32, shininess: f32) -> Self {
+        let total = diffuse.rgb.iter().fold(0.0_f32, |acc, x| acc.max(*x)) + specular;
+        let scale = match total > 1.0 {
+            true => 1.0 / total,
+            false => 1.0,
+        };
+
+        Self {
+            diffuse: diffuse * scale,
+            specular: specular * scale,
+            shininess,
+        }
+    }
+
+    // how often `sample` picks the highlight over the diffuse term
+    fn specular_chance(&self) -> f32 {
+        let diffuse = self.diffuse.rgb.iter().sum::<f32>() / 3.0;
+
+        match diffuse + self.specular > 0.0 {
+            true => self.specular / (diffuse + self.specular),
+            false => 0.0,
+        }
+    }
+
+    // the cosine between `wi` and the mirror direction of `wo`
+    fn highlight(&self, wo: Vector, wi: Vector, normal: Vector) -> f32 {
+        wo.negate().reflect(normal).dot(wi).max(0.0)
+    }
+}
+
+impl Bsdf for Phong {
+    fn eval(&self, wo: Vector, wi: Vector, normal: Vector) -> Color {
+        let cos = wi.dot(normal);
+        if wo.dot(normal) <= 0.0 || cos <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let specular = self.specular * (self.shininess + 2.0) / (2.0 * PI)
+            * self.highlight(wo, wi, normal).powf(self.shininess);
+        self.diffuse * (1.0 / PI) + Color::new(specular, specular, specular)
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample> {
+        let wi = match rng.next_f32() < self.specular_chance() {
+            true => power_cosine(wo.negate().reflect(normal), self.shininess, rng),
+            false => cosine_hemisphere(normal, rng),
+        };
+
+        BsdfSample::new(self, wo, wi, normal)
+    }
+
+    fn pdf(&self, wo: Vector, wi: Vector, normal: Vector) -> f32 {
+        let cos = wi.dot(normal);
+        if wo.dot(normal) <= 0.0 || cos <= 0.0 {
+            return 0.0;
+        }
+
+        let chance = self.specular_chance();
+        let lobe = (self.shininess + 1.0) / (2.0 * PI)
+            * self.highlight(wo, wi, normal).powf(self.shininess);
+        chance * lobe + (1.0 - chance) * cos / PI
+    }
+}
+
+// reflects everything in the mirror direction
+#[derive(Copy, Clone, Debug)]
+pub struct Mirror;
+
+impl Bsdf for Mirror {
+    fn eval(&self, _wo: Vector, _wi: Vector, _normal: Vector) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, _rng: &mut Rng) -> Option<BsdfSample> {
+        Some(BsdfSample {
+            direction: wo.negate().reflect(normal),
+            weight: Color::new(1.0, 1.0, 1.0),
+            pdf: 0.0,
+        })
+    }
+
+    fn pdf(&self, _wo: Vector, _wi: Vector, _normal: Vector) -> f32 {
+        0.0
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+// a smooth boundary between two refractive indices, like glass or water,
+// reflecting or refracting by the fresnel reflectance
+#[derive(Copy, Clone, Debug)]
+pub struct Dielectric {
+    // the index on the side `wo` is on and the one on the other side
+    pub n1: f32,
+    pub n2: f32,
+}
+
+impl Dielectric {
+    pub fn new(n1: f32, n2: f32) -> Self {
+        Self { n1, n2 }
+    }
+
+    // schlick's approximation of the share of light reflected when arriving at `cos` to the normal
+    pub fn reflectance(&self, cos: f32) -> f32 {
+        let mut cos = cos;
+
+        if self.n1 > self.n2 {
+            let n_ratio = self.n1 / self.n2;
+            let sin2_t = n_ratio.powi(2) * (1.0 - cos.powi(2));
+
+            // total internal reflection
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
+}
+
+impl Bsdf for Dielectric {
+    fn eval(&self, _wo: Vector, _wi: Vector, _normal: Vector) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample> {
+        let cos_i = wo.dot(normal);
+
+        let direction = match rng.next_f32() < self.reflectance(cos_i) {
+            true => wo.negate().reflect(normal),
+            false => {
+                let n_ratio = self.n1 / self.n2;
+                let cos_t = (1.0 - n_ratio.powi(2) * (1.0 - cos_i.powi(2))).sqrt();
+                normal * (n_ratio * cos_i - cos_t) - wo * n_ratio
+            }
+        };
+
+        Some(BsdfSample {
+            direction,
+            weight: Color::new(1.0, 1.0, 1.0),
+            pdf: 0.0,
+        })
+    }
+
+    fn pdf(&self, _wo: Vector, _wi: Vector, _normal: Vector) -> f32 {
+        0.0
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
+// the metallic/roughness model on a surface with a base color
+#[derive(Copy, Clone, Debug)]
+pub struct MicrofacetBsdf {
+    pub microfacet: Microfacet,
+    pub base: Color,
+}
+
+impl MicrofacetBsdf {
+    pub fn new(microfacet: Microfacet, base: Color) -> Self {
+        Self { microfacet, base }
+    }
+}
+
+impl Bsdf for MicrofacetBsdf {
+    fn eval(&self, wo: Vector, wi: Vector, normal: Vector) -> Color {
+        self.microfacet.eval(self.base, wo, wi, normal)
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample> {
+        BsdfSample::new(self, wo, self.microfacet.sample(wo, normal, rng), normal)
+    }
+
+    fn pdf(&self, wo: Vector, wi: Vector, normal: Vector) -> f32 {
+        self.microfacet.pdf(wo, wi, normal)
+    }
+}
+
+// one of the surface models above, so picking one per bounce needs no allocation
+#[derive(Copy, Clone, Debug)]
+pub enum SurfaceBsdf {
+    Lambertian(Lambertian),
+    Phong(Phong),
+    Mirror(Mirror),
+    Dielectric(Dielectric),
+    Microfacet(MicrofacetBsdf),
+}
+
+impl SurfaceBsdf {
+    fn inner(&self) -> &dyn Bsdf {
+        match self {
+            Self::Lambertian(bsdf) => bsdf,
+            Self::Phong(bsdf) => bsdf,
+            Self::Mirror(bsdf) => bsdf,
+            Self::Dielectric(bsdf) => bsdf,
+            Self::Microfacet(bsdf) => bsdf,
+        }
+    }
+}
+
+impl Bsdf for SurfaceBsdf {
+    fn eval(&self, wo: Vector, wi: Vector, normal: Vector) -> Color {
+        self.inner().eval(wo, wi, normal)
+    }
+
+    fn sample(&self, wo: Vector, normal: Vector, rng: &mut Rng) -> Option<BsdfSample> {
+        self.inner().sample(wo, normal, rng)
+    }
+
+    fn pdf(&self, wo: Vector, wi: Vector, normal: Vector) -> f32 {
+        self.inner().pdf(wo, wi, normal)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.inner().is_specular()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::sampling::uniform_hemisphere;
+    use crate::rendering::Material;
+
+    // the share of light from all over the hemisphere scattered towards `wo`, estimated
+    // once through `sample` and once by evaluating uniformly spread directions
+    fn albedo(bsdf: &dyn Bsdf, wo: Vector, samples: u32) -> (f32, f32) {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let mut rng = Rng::new(11);
+
+        let sampled = (0..samples)
+            .filter_map(|_| bsdf.sample(wo, normal, &mut rng))
+            .map(|sample| sample.weight.rgb[0])
+            .sum::<f32>()
+            / samples as f32;
+
+        let uniform = (0..samples)
+            .map(|_| {
+                let wi = uniform_hemisphere(normal, &mut rng);
+                bsdf.eval(wo, wi, normal).rgb[0] * wi.dot(normal)
+            })
+            .sum::<f32>()
+            * 2.0
+            * PI
+            / samples as f32;
+
+        (sampled, uniform)
+    }
+
+    #[test]
+    fn sampling_matches_evaluation() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let wo = Vector::new(0.0, 0.8, -0.6);
+        let bsdfs: [&dyn Bsdf; 3] = [
+            &Lambertian::new(white * 0.5),
+            &Phong::new(white * 0.5, 0.4, 10.0),
+            &MicrofacetBsdf::new(Microfacet::new(1.0, 0.6), white),
+        ];
+
+        for bsdf in bsdfs {
+            let (sampled, uniform) = albedo(bsdf, wo, 100000);
+
+            assert!(sampled <= 1.0);
+            assert!(
+                (sampled - uniform).abs() < 0.03,
+                "{} vs {}",
+                sampled,
+                uniform
+            );
+        }
+
+        let (lambertian, _) = albedo(&Lambertian::new(white * 0.5), wo, 100);
+        assert_eq!((lambertian * 10000.0).round(), 5000.0);
+    }
+
+    #[test]
+    fn default_material_conserves_energy() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let bsdf = Material::default().bsdf(white, 1.0, 1.0, &mut Rng::new(0));
+
+        for wo in [Vector::new(0.0, 1.0, 0.0), Vector::new(0.0, 0.6, -0.8)] {
+            let (sampled, uniform) = albedo(&bsdf, wo, 100000);
+
+            assert!(sampled <= 1.01, "{}", sampled);
+            assert!(uniform <= 1.01, "{}", uniform);
+        }
+    }
+
+    #[test]
+    fn mirror_reflects() {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let wo = Vector::new(0.6, 0.8, 0.0);
+        let sample = Mirror.sample(wo, normal, &mut Rng::new(0)).unwrap();
+
+        assert_eq!(
+            sample.direction.round(0.0001),
+            Vector::new(-0.6, 0.8, 0.0).round(0.0001)
+        );
+        assert_eq!(sample.pdf, 0.0);
+        assert!(Mirror.is_specular());
+    }
+
+    #[test]
+    fn dielectric_refracts_and_reflects() {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let glass = Dielectric::new(1.0, 1.5);
+        let mut rng = Rng::new(0);
+
+        // head on only about 4% is reflected, the rest passes straight through
+        let reflected = (0..1000)
+            .filter_map(|_| glass.sample(normal, normal, &mut rng))
+            .filter(|sample| sample.direction.dot(normal) > 0.0)
+            .count();
+        assert!((20..60).contains(&reflected), "{}", reflected);
+        assert_eq!(
+            glass
+                .sample(normal, normal, &mut Rng::new(1))
+                .unwrap()
+                .direction
+                .round(0.0001),
+            normal.negate()
+        );
+
+        // past the critical angle everything is reflected
+        let inside = Dielectric::new(1.5, 1.0);
+        let grazing = Vector::new(0.8, 0.6, 0.0);
+        assert_eq!(inside.reflectance(grazing.dot(normal)), 1.0);
+        assert!((0..100)
+            .filter_map(|_| inside.sample(grazing, normal, &mut rng))
+            .all(|sample| sample.direction.dot(normal) > 0.0));
+    }
+}
@@ -0,0 +1,275 @@
+use crate::math::{Bounds, Point};
+
+use super::{intersection::Intersection, Object, Ray};
+
+// how many buckets the centroids are sorted into when looking for the cheapest split
+const BUCKETS: usize = 12;
+// leaves with more objects than this are always split, even if the heuristic disagrees
+const MAX_LEAF_SIZE: usize = 4;
+// the cost of testing a ray against a node's box, relative to testing it against an object
+const TRAVERSAL_COST: f32 = 0.125;
+
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Bounds,
+    // a leaf's objects are `order[first..first + count]`, interior nodes have a count of 0,
+    // their left child right after them and their right child at `first`
+    first: usize,
+    count: usize,
+}
+
+// a bounding volume hierarchy over a list of objects, split using the surface area heuristic
+#[derive(Clone, Debug)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    // indices into the object list, grouped so every leaf owns a contiguous range
+    order: Vec<usize>,
+}
+
+struct Primitive {
+    index: usize,
+    bounds: Bounds,
+    centroid: Point,
+}
+
+impl Bvh {
+    pub fn new(objects: &[Object]) -> Self {
+        let mut primitives: Vec<Primitive> = objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| {
+                let bounds = object.bounds();
+                Primitive {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                }
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(objects.len() * 2),
+            order: Vec::with_capacity(objects.len()),
+        };
+
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives);
+        }
+
+        bvh
+    }
+
+    // how many objects the hierarchy was built over
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.nodes
+            .first()
+            .map_or(Bounds::empty(), |node| node.bounds)
+    }
+
+    // every intersection with the objects whose boxes the ray passes through, unsorted
+    pub fn intersect<'a>(&self, ray: &Ray, objects: &'a [Object]) -> Vec<Intersection<'a>> {
+        let mut intersections = Vec::new();
+        if self.nodes.is_empty() {
+            return intersections;
+        }
+
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+
+            if node.bounds.intersect(ray.origin, ray.direction).is_none() {
+                continue;
+            }
+
+            match node.count {
+                0 => stack.extend([index + 1, node.first]),
+                count => {
+                    for &i in &self.order[node.first..node.first + count] {
+                        ray.intersect_into(&objects[i], &mut intersections);
+                    }
+                }
+            }
+        }
+
+        intersections
+    }
+
+    fn build(&mut self, primitives: &mut [Primitive]) -> usize {
+        let index = self.nodes.len();
+        let bounds = primitives
+            .iter()
+            .fold(Bounds::empty(), |acc, p| acc.union(p.bounds));
+
+        self.nodes.push(Node {
+            bounds,
+            first: 0,
+            count: 0,
+        });
+
+        match self.split(primitives, bounds) {
+            Some(mid) => {
+                let (left, right) = primitives.split_at_mut(mid);
+                self.build(left);
+                self.nodes[index].first = self.build(right);
+            }
+            None => {
+                self.nodes[index].first = self.order.len();
+                self.nodes[index].count = primitives.len();
+                self.order.extend(primitives.iter().map(|p| p.index));
+            }
+        }
+
+        index
+    }
+
+    // partitions the primitives along the cheapest bucket boundary and returns where
+    // the right half starts, or none if the primitives are better off in one leaf
+    fn split(&self, primitives: &mut [Primitive], bounds: Bounds) -> Option<usize> {
+        if primitives.len() <= 1 {
+            return None;
+        }
+
+        let centroids = primitives
+            .iter()
+            .fold(Bounds::empty(), |acc, p| acc.add_point(p.centroid));
+        let axis = centroids.longest_axis();
+        let min = centroids.min.xyz[axis];
+        let extent = centroids.max.xyz[axis] - min;
+
+        // every centroid is in the same spot, so there's nothing to split on
+        if extent <= 0.0 {
+            return None;
+        }
+
+        let bucket = |p: &Primitive| {
+            let offset = (p.centroid.xyz[axis] - min) / extent;
+            ((offset * BUCKETS as f32) as usize).min(BUCKETS - 1)
+        };
+
+        let mut buckets = [(0, Bounds::empty()); BUCKETS];
+        for primitive in primitives.iter() {
+            let (count, bucket_bounds) = &mut buckets[bucket(primitive)];
+            *count += 1;
+            *bucket_bounds = bucket_bounds.union(primitive.bounds);
+        }
+
+        // cost of splitting after each bucket, in units of object intersection tests
+        let (best, cost) = (0..BUCKETS - 1)
+            .map(|split| {
+                let side = |range: &[(usize, Bounds)]| {
+                    range
+                        .iter()
+                        .fold((0, Bounds::empty()), |(n, acc), (count, b)| {
+                            (n + count, acc.union(*b))
+                        })
+                };
+                let (left_count, left) = side(&buckets[..=split]);
+                let (right_count, right) = side(&buckets[split + 1..]);
+
+                let cost = TRAVERSAL_COST
+                    + (left_count as f32 * left.surface_area()
+                        + right_count as f32 * right.surface_area())
+                        / bounds.surface_area();
+
+                (split, cost)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        if primitives.len() <= MAX_LEAF_SIZE && cost >= primitives.len() as f32 {
+            return None;
+        }
+
+        primitives.sort_unstable_by_key(|p| bucket(p) > best);
+        let mid = primitives.partition_point(|p| bucket(p) <= best);
+
+        match mid {
+            0 => None,
+            mid if mid == primitives.len() => None,
+            mid => Some(mid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::Matrix4x4;
+    use crate::rendering::Material;
+
+    fn spheres(count: i32) -> Vec<Object> {
+        (0..count)
+            .map(|i| {
+                let mut sphere = Object::new_sphere(
+                    Material::default(),
+                    Matrix4x4::identity().translate(
+                        (i % 10) as f32 * 3.0,
+                        (i / 10) as f32 * 3.0,
+                        0.0,
+                    ),
+                );
+                sphere.calc_inverse_transform();
+                sphere.calc_inverse_transpose_transform();
+                sphere
+            })
+            .collect()
+    }
+
+    #[test]
+    fn contains_every_object_once() {
+        let objects = spheres(100);
+        let bvh = Bvh::new(&objects);
+
+        let mut order = bvh.order.clone();
+        order.sort();
+
+        assert_eq!(order, (0..100).collect::<Vec<_>>());
+        assert!(bvh.nodes.iter().all(|node| node.count <= MAX_LEAF_SIZE));
+        assert_eq!(
+            bvh.bounds(),
+            Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(28.0, 28.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn intersect_matches_brute_force() {
+        let objects = spheres(100);
+        let bvh = Bvh::new(&objects);
+
+        for (x, y) in [(0.0, 0.0), (6.5, 9.0), (27.0, 27.5), (1.5, 1.5)] {
+            let ray = Ray::new(x, y, -5.0, 0.0, 0.0, 1.0);
+
+            let mut expected: Vec<f32> = objects
+                .iter()
+                .flat_map(|object| ray.intersection(object))
+                .map(|i| i.time)
+                .collect();
+            let mut actual: Vec<f32> = bvh
+                .intersect(&ray, &objects)
+                .iter()
+                .map(|i| i.time)
+                .collect();
+
+            expected.sort_by(f32::total_cmp);
+            actual.sort_by(f32::total_cmp);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let bvh = Bvh::new(&[]);
+
+        assert!(bvh.is_empty());
+        assert!(bvh
+            .intersect(&Ray::new(0.0, 0.0, 0.0, 0.0, 0.0, 1.0), &[])
+            .is_empty());
+    }
+}
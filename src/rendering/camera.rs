@@ -0,0 +1,384 @@
+use std::{f32::consts::PI, io, str::FromStr};
+
+use crate::math::{sampling, Matrix4x4, Point, Rng, Vector};
+
+use super::{renderer::Renderer, world::World, Canvas, Color, Ray};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Projection {
+    // depth of field only applies to perspective cameras
+    Perspective,
+    // parallel rays over a view `width` world units wide, for technical drawings
+    Orthographic { width: f32 },
+    // the whole sphere around the camera, for 360 degree panoramas
+    Equirectangular,
+    // a circular image covering the camera's field of view, which can go past 180 degrees
+    Fisheye,
+}
+
+// parses the names scene files use, "orthographic" is followed by the width of its view
+impl FromStr for Projection {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let mut words = s.split_whitespace();
+        let projection = match words.next() {
+            Some("perspective") => Self::Perspective,
+            Some("orthographic") => {
+                let width = words
+                    .next()
+                    .and_then(|width| width.parse().ok())
+                    .filter(|width: &f32| *width > 0.0)
+                    .ok_or_else(|| {
+                        invalid_input("orthographic projections need a positive width")
+                    })?;
+
+                Self::Orthographic { width }
+            }
+            Some("equirectangular") => Self::Equirectangular,
+            Some("fisheye") => Self::Fisheye,
+            _ => return Err(invalid_input(&format!("unknown projection {:?}", s))),
+        };
+
+        match words.next() {
+            Some(extra) => Err(invalid_input(&format!(
+                "unexpected {:?} after projection",
+                extra
+            ))),
+            None => Ok(projection),
+        }
+    }
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+pub struct Camera {
+    pub hsize: i32,
+    pub vsize: i32,
+    pub field_of_view: f32,
+    pub transform: Matrix4x4,
+    pub inverse_transform: Matrix4x4,
+    pub projection: Projection,
+    // radius of the lens, zero gives a pinhole camera where everything is in focus
+    pub aperture: f32,
+    // how far in front of the camera things are perfectly sharp
+    pub focal_distance: f32,
+    // rays are cast at times spread evenly between the shutter opening and closing,
+    // so objects that move in that time are blurred along their path
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+    half_width: f32,
+    half_height: f32,
+    pixel_size: f32,
+}
+
+impl Camera {
+    pub fn new(hsize: i32, vsize: i32, field_of_view: f32, transform: Matrix4x4) -> Self {
+        // the canvas is one unit in front of the camera
+        let half_view = (field_of_view / 2.0).tan();
+        let aspect = hsize as f32 / vsize as f32;
+        let (half_width, half_height) = match aspect >= 1.0 {
+            true => (half_view, half_view / aspect),
+            false => (half_view * aspect, half_view),
+        };
+
+        Self {
+            hsize,
+            vsize,
+            field_of_view,
+            transform,
+            inverse_transform: transform.inverse().unwrap(),
+            projection: Projection::Perspective,
+            aperture: 0.0,
+            focal_distance: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
+            half_width,
+            half_height,
+            pixel_size: half_width * 2.0 / hsize as f32,
+        }
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix4x4) {
+        self.transform = transform;
+        self.inverse_transform = transform.inverse().unwrap();
+    }
+
+    // a ray through canvas position (x, y), where pixel centers are at +0.5,
+    // or none if the projection doesn't cover that part of the canvas
+    pub fn ray_for_pixel(&self, x: f32, y: f32, rng: &mut Rng) -> Option<Ray> {
+        // camera space, looking towards -z with +x to the left
+        let (origin, direction) = match self.projection {
+            Projection::Perspective => self.perspective(x, y, rng),
+            Projection::Orthographic { width } => {
+                let pixel_size = width / self.hsize as f32;
+                let origin = Point::new(
+                    (self.hsize as f32 / 2.0 - x) * pixel_size,
+                    (self.vsize as f32 / 2.0 - y) * pixel_size,
+                    0.0,
+                );
+
+                (origin, Vector::new(0.0, 0.0, -1.0))
+            }
+            Projection::Equirectangular => {
+                // the canvas spans all the way around horizontally and from pole to pole
+                let longitude = (x / self.hsize as f32 - 0.5) * 2.0 * PI;
+                let latitude = (0.5 - y / self.vsize as f32) * PI;
+                let direction = Vector::new(
+                    -longitude.sin() * latitude.cos(),
+                    latitude.sin(),
+                    -longitude.cos() * latitude.cos(),
+                );
+
+                (Point::zero(), direction)
+            }
+            Projection::Fisheye => {
+                // equidistant, the angle from the view direction grows linearly towards
+                // the edge of the largest circle that fits on the canvas
+                let radius = self.hsize.min(self.vsize) as f32 / 2.0;
+                let dx = (x - self.hsize as f32 / 2.0) / radius;
+                let dy = (y - self.vsize as f32 / 2.0) / radius;
+                let distance = (dx * dx + dy * dy).sqrt();
+
+                if distance > 1.0 {
+                    return None;
+                }
+
+                let theta = distance * self.field_of_view / 2.0;
+                let (sin, cos) = theta.sin_cos();
+                let direction = match distance > 0.0 {
+                    true => Vector::new(-dx / distance * sin, -dy / distance * sin, -cos),
+                    false => Vector::new(0.0, 0.0, -1.0),
+                };
+
+                (Point::zero(), direction)
+            }
+        };
+
+        let time = match self.shutter_close > self.shutter_open {
+            true => self.shutter_open + (self.shutter_close - self.shutter_open) * rng.next_f32(),
+            false => self.shutter_open,
+        };
+
+        Some(
+            Ray::point_vector(
+                origin * self.inverse_transform,
+                (direction * self.inverse_transform).normalize(),
+            )
+            .with_shutter_time(time),
+        )
+    }
+
+    // with an aperture the ray starts somewhere on the lens and passes through the
+    // point on the focal plane the pinhole ray would have hit
+    fn perspective(&self, x: f32, y: f32, rng: &mut Rng) -> (Point, Vector) {
+        let world_x = self.half_width - x * self.pixel_size;
+        let world_y = self.half_height - y * self.pixel_size;
+
+        let focus = Point::new(
+            world_x * self.focal_distance,
+            world_y * self.focal_distance,
+            -self.focal_distance,
+        );
+
+        let lens = match self.aperture > 0.0 {
+            true => {
+                let (x, y) = sampling::unit_disk(rng);
+                Point::new(x * self.aperture, y * self.aperture, 0.0)
+            }
+            false => Point::zero(),
+        };
+
+        (lens, focus - lens)
+    }
+
+    pub fn render(&self, world: &World, renderer: &Renderer) -> Canvas {
+        let mut canvas = Canvas::new(self.hsize, self.vsize, Color::new(0.0, 0.0, 0.0));
+
+        renderer.render_sampled(&mut canvas, |x, y, rng| {
+            self.ray_for_pixel(x, y, rng)
+                .map_or(Color::new(0.0, 0.0, 0.0), |ray| {
+                    renderer.integrator.radiance(world, &ray, rng)
+                })
+        });
+
+        canvas
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::util::about_equals;
+
+    #[test]
+    fn pixel_size() {
+        let horizontal = Camera::new(200, 125, PI / 2.0, Matrix4x4::identity());
+        let vertical = Camera::new(125, 200, PI / 2.0, Matrix4x4::identity());
+
+        assert_eq!((horizontal.pixel_size * 100.0).round(), 1.0);
+        assert_eq!((vertical.pixel_size * 100.0).round(), 1.0);
+    }
+
+    #[test]
+    fn ray_for_pixel() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(201, 101, PI / 2.0, Matrix4x4::identity());
+
+        let ray = camera.ray_for_pixel(100.5, 50.5, &mut rng).unwrap();
+        assert_eq!(ray.origin, Point::zero());
+        assert_eq!(ray.direction.round(0.0001), Vector::new(0.0, 0.0, -1.0));
+
+        let ray = camera.ray_for_pixel(0.5, 0.5, &mut rng).unwrap();
+        assert_eq!(
+            ray.direction.round(0.0001),
+            Vector::new(0.66519, 0.33259, -0.66851).round(0.0001)
+        );
+
+        camera.set_transform(
+            Matrix4x4::identity()
+                .rotate_y(PI / 4.0)
+                .translate(0.0, -2.0, 5.0),
+        );
+        let ray = camera.ray_for_pixel(100.5, 50.5, &mut rng).unwrap();
+        assert_eq!(ray.origin.round(0.0001), Point::new(0.0, 2.0, -5.0));
+        assert_eq!(
+            ray.direction.round(0.0001),
+            Vector::new(2.0_f32.sqrt() / 2.0, 0.0, -(2.0_f32.sqrt()) / 2.0).round(0.0001)
+        );
+    }
+
+    #[test]
+    fn shutter_times() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(11, 11, PI / 3.0, Matrix4x4::identity());
+
+        assert_eq!(
+            camera
+                .ray_for_pixel(5.5, 5.5, &mut rng)
+                .unwrap()
+                .shutter_time,
+            0.0
+        );
+
+        camera.shutter_open = 0.25;
+        camera.shutter_close = 0.75;
+        let times: Vec<f32> = (0..32)
+            .map(|_| {
+                camera
+                    .ray_for_pixel(5.5, 5.5, &mut rng)
+                    .unwrap()
+                    .shutter_time
+            })
+            .collect();
+
+        assert!(times.iter().all(|time| (0.25..0.75).contains(time)));
+        assert!(times.iter().any(|time| *time < 0.5) && times.iter().any(|time| *time > 0.5));
+    }
+
+    #[test]
+    fn thin_lens_converges_on_focal_plane() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(11, 11, PI / 3.0, Matrix4x4::identity());
+        camera.aperture = 0.5;
+        camera.focal_distance = 4.0;
+
+        let mut focus = None;
+        let mut origins = Vec::new();
+
+        for _ in 0..16 {
+            let ray = camera.ray_for_pixel(2.5, 7.5, &mut rng).unwrap();
+            let time = -camera.focal_distance / ray.direction.xyz[2];
+            let point = ray.position_at(time);
+
+            let expected = *focus.get_or_insert(point);
+            assert!((0..3).all(|i| about_equals(point.xyz[i], expected.xyz[i], 0.001)));
+
+            assert!((ray.origin - Point::zero()).magnitude() <= camera.aperture);
+            assert_eq!(ray.origin.xyz[2], 0.0);
+            origins.push(ray.origin);
+        }
+
+        // the origins are spread over the lens rather than all at its center
+        assert!(origins.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(200, 100, PI / 2.0, Matrix4x4::identity());
+        camera.projection = Projection::Orthographic { width: 10.0 };
+
+        let center = camera.ray_for_pixel(100.0, 50.0, &mut rng).unwrap();
+        let corner = camera.ray_for_pixel(0.0, 0.0, &mut rng).unwrap();
+
+        assert_eq!(center.origin, Point::zero());
+        assert_eq!(corner.origin, Point::new(5.0, 2.5, 0.0));
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(corner.direction, center.direction);
+    }
+
+    #[test]
+    fn equirectangular_wraps_around() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(400, 200, PI / 2.0, Matrix4x4::identity());
+        camera.projection = Projection::Equirectangular;
+
+        let direction = |x, y, rng: &mut Rng| {
+            camera
+                .ray_for_pixel(x, y, rng)
+                .unwrap()
+                .direction
+                .round(0.0001)
+        };
+
+        assert_eq!(
+            direction(200.0, 100.0, &mut rng),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+        // a quarter of the way across looks to the left, and the edges look behind
+        assert_eq!(
+            direction(100.0, 100.0, &mut rng),
+            Vector::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(direction(0.0, 100.0, &mut rng), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(direction(200.0, 0.0, &mut rng), Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn fisheye_covers_a_circle() {
+        let mut rng = Rng::new(0);
+        let mut camera = Camera::new(200, 100, PI, Matrix4x4::identity());
+        camera.projection = Projection::Fisheye;
+
+        let center = camera.ray_for_pixel(100.0, 50.0, &mut rng).unwrap();
+        let edge = camera.ray_for_pixel(150.0, 50.0, &mut rng).unwrap();
+
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        // a 180 degree lens sees straight to the side at the rim
+        assert_eq!(edge.direction.round(0.0001), Vector::new(-1.0, 0.0, 0.0));
+        assert!(camera.ray_for_pixel(10.0, 10.0, &mut rng).is_none());
+    }
+
+    #[test]
+    fn parse_projection() {
+        assert_eq!(
+            "perspective".parse::<Projection>().unwrap(),
+            Projection::Perspective
+        );
+        assert_eq!(
+            " orthographic 12.5 ".parse::<Projection>().unwrap(),
+            Projection::Orthographic { width: 12.5 }
+        );
+        assert_eq!(
+            "fisheye".parse::<Projection>().unwrap(),
+            Projection::Fisheye
+        );
+        assert!("orthographic".parse::<Projection>().is_err());
+        assert!("orthographic -1".parse::<Projection>().is_err());
+        assert!("fisheye 2".parse::<Projection>().is_err());
+        assert!("isometric".parse::<Projection>().is_err());
+    }
+}
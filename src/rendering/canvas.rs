@@ -1,61 +1,365 @@
 use std::fmt::Write;
-
-use unroll::unroll_for_loops;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
 
 use super::Color;
 
+#[derive(PartialEq, Debug)]
 pub struct Canvas {
     pub width: i32,
     pub height: i32,
-    pub pixels: Vec<Vec<Color>>,
+    // row-major, the pixel at (x, y) is at y * width + x
+    pub pixels: Vec<Color>,
 }
 
 impl Canvas {
-    // create a new canvas with all pixels set to black
-    #[unroll_for_loops]
+    // create a new canvas with every pixel set to `color`
     pub fn new(width: i32, height: i32, color: Color) -> Self {
-        let mut pixels = Vec::new();
+        Self {
+            width,
+            height,
+            pixels: vec![color; width.max(0) as usize * height.max(0) as usize],
+        }
+    }
+
+    pub fn pixel_at(&self, x: i32, y: i32) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn write_pixel(&mut self, x: i32, y: i32, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    // checks both coordinates, since an x past the width would otherwise
+    // silently land on the next row
+    fn index(&self, x: i32, y: i32) -> usize {
+        assert!(
+            (0..self.width).contains(&x) && (0..self.height).contains(&y),
+            "pixel ({}, {}) is outside of the {}x{} canvas",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+
+        (y * self.width + x) as usize
+    }
+
+    // loads a ppm or png image, picking the decoder from the file's magic bytes
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+
+        if data.starts_with(b"\x89PNG") {
+            Self::from_png(&data)
+        } else {
+            Self::from_ppm(&data)
+        }
+    }
+
+    // parses both plain (P3) and binary (P6) ppm files
+    pub fn from_ppm(data: &[u8]) -> io::Result<Self> {
+        let mut cursor = 0;
+        let magic = ppm_token(data, &mut cursor)?;
+        let binary = match magic {
+            "P3" => false,
+            "P6" => true,
+            _ => return Err(invalid_data("unsupported ppm magic number")),
+        };
+
+        let width: usize = ppm_number(data, &mut cursor)?;
+        let height: usize = ppm_number(data, &mut cursor)?;
+        let max_value: u32 = ppm_number(data, &mut cursor)?;
+
+        if width == 0 || height == 0 || max_value == 0 || max_value > 65535 {
+            return Err(invalid_data("invalid ppm header"));
+        }
+
+        // binary samples start after exactly one whitespace byte following the header
+        cursor += 1;
+        let wide = max_value > 255;
 
-        for i in 0..(width) {
-            pixels.push(Vec::new());
-            for _ in 0..(height) {
-                pixels[i as usize].push(color);
+        // every sample takes at least one byte, so a header claiming more pixels than
+        // the data could hold is rejected before anything is allocated for them
+        let sample_size = match binary && wide {
+            true => 2,
+            false => 1,
+        };
+        let remaining = data.len().saturating_sub(cursor);
+        let fits = width
+            .checked_mul(height)
+            .filter(|pixels| i32::try_from(*pixels).is_ok())
+            .and_then(|pixels| pixels.checked_mul(3 * sample_size))
+            .is_some_and(|size| size <= remaining);
+        if !fits {
+            return Err(invalid_data("ppm size doesn't match its data"));
+        }
+
+        let mut canvas = Self::new(width as i32, height as i32, Color::new(0.0, 0.0, 0.0));
+
+        for y in 0..height {
+            for x in 0..width {
+                let mut rgb = [0.0; 3];
+                for channel in &mut rgb {
+                    let value = if !binary {
+                        ppm_number(data, &mut cursor)?
+                    } else if wide {
+                        let bytes = data
+                            .get(cursor..cursor + 2)
+                            .ok_or_else(|| invalid_data("truncated ppm data"))?;
+                        cursor += 2;
+                        u32::from(bytes[0]) << 8 | u32::from(bytes[1])
+                    } else {
+                        let byte = *data
+                            .get(cursor)
+                            .ok_or_else(|| invalid_data("truncated ppm data"))?;
+                        cursor += 1;
+                        u32::from(byte)
+                    };
+                    *channel = value.min(max_value) as f32 / max_value as f32;
+                }
+                canvas.write_pixel(x as i32, y as i32, Color::new_arr(rgb));
             }
         }
 
-        Self {
-            width,
-            height,
-            pixels,
+        Ok(canvas)
+    }
+
+    // decodes any png color type into 8 bit rgb, dropping the alpha channel
+    pub fn from_png(data: &[u8]) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(data);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+
+        let channels = match info.color_type {
+            png::ColorType::Grayscale => 1,
+            png::ColorType::GrayscaleAlpha => 2,
+            png::ColorType::Rgb => 3,
+            png::ColorType::Rgba => 4,
+            png::ColorType::Indexed => return Err(invalid_data("unexpanded indexed png")),
+        };
+
+        let width = info.width as usize;
+        let height = info.height as usize;
+        let mut canvas = Self::new(width as i32, height as i32, Color::new(0.0, 0.0, 0.0));
+
+        for y in 0..height {
+            let row = &buffer[y * info.line_size..];
+            for x in 0..width {
+                let texel = &row[x * channels..];
+                let rgb = match channels {
+                    1 | 2 => [texel[0]; 3],
+                    _ => [texel[0], texel[1], texel[2]],
+                };
+                canvas.write_pixel(
+                    x as i32,
+                    y as i32,
+                    Color::new_arr(rgb.map(|c| c as f32 / 255.0)),
+                );
+            }
         }
+
+        Ok(canvas)
     }
 
-    #[unroll_for_loops]
+    // writes the rows top to bottom, wrapping lines before they pass 70 characters
     pub fn to_ppm(&self) -> String {
         let size: usize = (self.width * self.height * 12 + 16) as usize;
         let mut result = String::with_capacity(size); // pre-allocate buffer
 
         write!(&mut result, "P3\n{} {}\n255\n", self.width, self.height).unwrap();
 
-        self.pixels.iter().for_each(|row| {
-            row.iter().for_each(|pixel| {
-                let rgb = pixel.to_rgb();
+        for row in self.pixels.chunks(self.width.max(1) as usize) {
+            let mut line_length = 0;
 
-                if !result.ends_with('\n') {
-                    result.push(' ');
-                }
+            for value in row.iter().flat_map(|pixel| pixel.to_rgb_values()) {
+                let value = value.to_string();
 
-                if result.len() - result.rfind('\n').unwrap_or(0) + rgb.len() > 70 {
+                if line_length > 0 && line_length + 1 + value.len() > 70 {
                     result.push('\n');
+                    line_length = 0;
                 }
 
-                result.push_str(&rgb);
-                result.push(' ');
-            });
+                if line_length > 0 {
+                    result.push(' ');
+                    line_length += 1;
+                }
+
+                result.push_str(&value);
+                line_length += value.len();
+            }
 
             result.push('\n');
-        });
+        }
 
         result
     }
 }
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// returns the next whitespace separated token, skipping '#' comments
+fn ppm_token<'a>(data: &'a [u8], cursor: &mut usize) -> io::Result<&'a str> {
+    loop {
+        match data.get(*cursor) {
+            Some(b'#') => {
+                while !matches!(data.get(*cursor), Some(b'\n') | None) {
+                    *cursor += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => *cursor += 1,
+            Some(_) => break,
+            None => return Err(invalid_data("unexpected end of ppm data")),
+        }
+    }
+
+    let start = *cursor;
+    while matches!(data.get(*cursor), Some(c) if !c.is_ascii_whitespace()) {
+        *cursor += 1;
+    }
+
+    std::str::from_utf8(&data[start..*cursor]).map_err(|_| invalid_data("invalid ppm token"))
+}
+
+fn ppm_number<T: FromStr>(data: &[u8], cursor: &mut usize) -> io::Result<T> {
+    ppm_token(data, cursor)?
+        .parse()
+        .map_err(|_| invalid_data("invalid ppm number"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pixels_are_row_major() {
+        let mut canvas = Canvas::new(3, 2, Color::new(0.0, 0.0, 0.0));
+        canvas.write_pixel(2, 0, Color::new(1.0, 0.0, 0.0));
+        canvas.write_pixel(0, 1, Color::new(0.0, 1.0, 0.0));
+
+        assert_eq!(canvas.pixels.len(), 6);
+        assert_eq!(canvas.pixels[2], Color::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.pixels[3], Color::new(0.0, 1.0, 0.0));
+        assert_eq!(canvas.pixel_at(2, 0), Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the 3x2 canvas")]
+    fn write_pixel_out_of_bounds() {
+        let mut canvas = Canvas::new(3, 2, Color::new(0.0, 0.0, 0.0));
+        canvas.write_pixel(3, 0, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn to_ppm_non_square() {
+        let mut canvas = Canvas::new(3, 2, Color::new(0.0, 0.0, 0.0));
+        canvas.write_pixel(0, 0, Color::new(1.5, 0.0, 0.0));
+        canvas.write_pixel(2, 0, Color::new(0.0, 0.5, 0.0));
+        canvas.write_pixel(1, 1, Color::new(-0.5, 0.0, 1.0));
+
+        assert_eq!(
+            canvas.to_ppm(),
+            "P3\n3 2\n255\n255 0 0 0 0 0 0 128 0\n0 0 0 0 0 255 0 0 0\n"
+        );
+    }
+
+    #[test]
+    fn to_ppm_wraps_long_lines() {
+        let canvas = Canvas::new(10, 2, Color::new(1.0, 0.8, 0.6));
+        let ppm = canvas.to_ppm();
+        let lines: Vec<&str> = ppm.lines().skip(3).collect();
+        let row = "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204";
+
+        assert_eq!(
+            lines,
+            [
+                row,
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+                row,
+                "153 255 204 153 255 204 153 255 204 153 255 204 153",
+            ]
+        );
+        assert!(ppm.ends_with('\n'));
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let mut canvas = Canvas::new(4, 3, Color::new(0.0, 0.0, 0.0));
+        canvas.write_pixel(3, 1, Color::new(1.0, 0.0, 1.0));
+
+        assert_eq!(
+            Canvas::from_ppm(canvas.to_ppm().as_bytes()).unwrap(),
+            canvas
+        );
+    }
+
+    #[test]
+    fn from_ppm_plain() {
+        let ppm = b"P3\n# a comment\n4 3\n255\n\
+            255 127 0  0 127 255  127 255 0  255 255 255\n\
+            0 0 0  255 0 0  0 255 0  0 0 255\n\
+            255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let canvas = Canvas::from_ppm(ppm).unwrap();
+
+        assert_eq!((canvas.width, canvas.height), (4, 3));
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 127.0 / 255.0, 0.0));
+        assert_eq!(canvas.pixel_at(1, 1), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            canvas.pixel_at(3, 2),
+            Color::new(127.0 / 255.0, 127.0 / 255.0, 127.0 / 255.0)
+        );
+    }
+
+    #[test]
+    fn from_ppm_binary() {
+        let mut ppm = b"P6\n2 1\n100\n".to_vec();
+        ppm.extend_from_slice(&[100, 50, 0, 0, 0, 100]);
+        let canvas = Canvas::from_ppm(&ppm).unwrap();
+
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.5, 0.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn from_png_rgba() {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer
+            .write_image_data(&[255, 0, 0, 255, 0, 255, 255, 0])
+            .unwrap();
+        writer.finish().unwrap();
+
+        let canvas = Canvas::from_png(&png).unwrap();
+
+        assert_eq!((canvas.width, canvas.height), (2, 1));
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(0.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn from_ppm_rejects_bad_magic() {
+        assert!(Canvas::from_ppm(b"P4\n1 1\n255\n0 0 0").is_err());
+        assert!(Canvas::from_ppm(b"P3\n2 2\n255\n0 0 0").is_err());
+    }
+
+    #[test]
+    fn from_ppm_rejects_oversized_headers() {
+        // the product overflows both i32 and usize
+        assert!(Canvas::from_ppm(b"P6\n4294967296 4294967296\n255\n\0\0\0").is_err());
+        assert!(Canvas::from_ppm(b"P6\n65536 65536\n255\n\0\0\0").is_err());
+        assert!(Canvas::from_ppm(b"P3\n-1 1\n255\n0 0 0").is_err());
+        // the header promises more pixels than there are bytes
+        assert!(Canvas::from_ppm(b"P6\n2 1\n65535\n\0\0\0\0\0\0").is_err());
+    }
+}
@@ -14,10 +14,19 @@ impl Color {
         Self { rgb }
     }
 
+    pub fn round(&self, epsilon: f32) -> Self {
+        Self::new_arr(self.rgb.map(|v| (v / epsilon).round() * epsilon))
+    }
+
     pub fn to_rgb(&self) -> String {
-        let rgb = self.rgb.map(|x| (x * 255.0).round() as u8);
+        let rgb = self.to_rgb_values();
         format!("{} {} {}", rgb[0], rgb[1], rgb[2])
     }
+
+    // scales each channel to 0-255, clamping values outside of [0, 1]
+    pub fn to_rgb_values(self) -> [u8; 3] {
+        self.rgb.map(|x| (x * 255.0).round() as u8)
+    }
 }
 
 impl Add<Self> for Color {
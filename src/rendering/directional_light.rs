@@ -0,0 +1,41 @@
+use crate::math::{Point, Rng, Vector};
+
+use super::{world::World, Color, Light, LightSample};
+
+// a light infinitely far away, like the sun, so every point sees it from the same direction
+#[derive(Copy, Clone)]
+pub struct DirectionalLight {
+    // the direction the light travels in
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        Self {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(
+        &self,
+        _world: &World,
+        _point: Point,
+        _time: f32,
+        _rng: &mut Rng,
+    ) -> Vec<LightSample> {
+        vec![LightSample::new(
+            self.direction.negate(),
+            f32::INFINITY,
+            self.intensity,
+            Color::new(1.0, 1.0, 1.0),
+        )]
+    }
+}
@@ -0,0 +1,168 @@
+use std::f32::consts::PI;
+
+use crate::math::Vector;
+
+use super::{pattern::Texture, Color};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CubeFace {
+    Left,
+    Front,
+    Right,
+    Back,
+    Up,
+    Down,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum EnvironmentMap {
+    // faces are indexed in the order of `CubeFace`
+    CubeMap([Texture; 6]),
+    Equirectangular(Texture),
+}
+
+// what rays see when they leave the scene without hitting anything
+#[derive(Clone, PartialEq, Debug)]
+pub struct Environment {
+    pub map: EnvironmentMap,
+    pub intensity: f32,
+    // whether reflected and refracted rays see the environment too, rather than black
+    pub in_reflections: bool,
+}
+
+impl CubeFace {
+    // the face is picked by the largest component of the direction
+    pub fn from_direction(direction: Vector) -> Self {
+        let [x, y, z] = direction.xyz;
+        let coord = x.abs().max(y.abs()).max(z.abs());
+
+        if coord == x {
+            Self::Right
+        } else if coord == -x {
+            Self::Left
+        } else if coord == y {
+            Self::Up
+        } else if coord == -y {
+            Self::Down
+        } else if coord == z {
+            Self::Front
+        } else {
+            Self::Back
+        }
+    }
+
+    // expects a point on the surface of the [-1, 1] cube
+    pub fn uv(&self, point: [f32; 3]) -> (f32, f32) {
+        let [x, y, z] = point;
+
+        let (u, v) = match self {
+            Self::Front => (x + 1.0, y + 1.0),
+            Self::Back => (1.0 - x, y + 1.0),
+            Self::Left => (z + 1.0, y + 1.0),
+            Self::Right => (1.0 - z, y + 1.0),
+            Self::Up => (x + 1.0, 1.0 - z),
+            Self::Down => (x + 1.0, z + 1.0),
+        };
+
+        (u / 2.0, v / 2.0)
+    }
+}
+
+impl EnvironmentMap {
+    pub fn color_at(&self, direction: Vector) -> Color {
+        match self {
+            Self::CubeMap(faces) => {
+                let face = CubeFace::from_direction(direction);
+                let coord = direction
+                    .xyz
+                    .iter()
+                    .fold(0.0_f32, |acc, x| acc.max(x.abs()));
+                let (u, v) = face.uv(direction.xyz.map(|x| x / coord));
+
+                faces[face as usize].sample(u, v)
+            }
+            Self::Equirectangular(texture) => {
+                let direction = direction.normalize();
+                let [x, y, z] = direction.xyz;
+                let u = 0.5 + x.atan2(z) / (2.0 * PI);
+                let v = 0.5 + y.clamp(-1.0, 1.0).asin() / PI;
+
+                texture.sample(u, v)
+            }
+        }
+    }
+}
+
+impl Environment {
+    pub fn new(map: EnvironmentMap) -> Self {
+        Self {
+            map,
+            intensity: 1.0,
+            in_reflections: true,
+        }
+    }
+
+    pub fn color_at(&self, direction: Vector) -> Color {
+        self.map.color_at(direction) * self.intensity
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::rendering::{
+        pattern::{Addressing, Filter},
+        Canvas,
+    };
+
+    fn solid(color: Color) -> Texture {
+        Texture::new(
+            Arc::new(Canvas::new(1, 1, color)),
+            Filter::Nearest,
+            Addressing::Clamp,
+        )
+    }
+
+    #[test]
+    fn cube_face_from_direction() {
+        let cases = [
+            (Vector::new(-1.0, 0.5, -0.25), CubeFace::Left),
+            (Vector::new(1.1, -0.75, 0.8), CubeFace::Right),
+            (Vector::new(0.1, 0.6, 0.9), CubeFace::Front),
+            (Vector::new(-0.7, 0.0, -2.0), CubeFace::Back),
+            (Vector::new(0.5, 1.0, 0.9), CubeFace::Up),
+            (Vector::new(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+
+        for (direction, face) in cases {
+            assert_eq!(CubeFace::from_direction(direction), face);
+        }
+    }
+
+    #[test]
+    fn cube_face_uv() {
+        assert_eq!(CubeFace::Front.uv([-0.5, 0.5, 1.0]), (0.25, 0.75));
+        assert_eq!(CubeFace::Back.uv([0.5, -0.5, -1.0]), (0.25, 0.25));
+        assert_eq!(CubeFace::Up.uv([-0.5, 1.0, -0.5]), (0.25, 0.75));
+        assert_eq!(CubeFace::Down.uv([0.5, -1.0, 0.5]), (0.75, 0.75));
+    }
+
+    #[test]
+    fn cube_map_samples_faces() {
+        let colors = [
+            Color::new(1.0, 0.0, 0.0),
+            Color::new(0.0, 1.0, 0.0),
+            Color::new(0.0, 0.0, 1.0),
+            Color::new(1.0, 1.0, 0.0),
+            Color::new(1.0, 0.0, 1.0),
+            Color::new(0.0, 1.0, 1.0),
+        ];
+        let environment = Environment::new(EnvironmentMap::CubeMap(colors.map(solid)));
+
+        assert_eq!(environment.color_at(Vector::new(-3.0, 1.0, 0.0)), colors[0]);
+        assert_eq!(environment.color_at(Vector::new(0.0, 0.0, 1.0)), colors[1]);
+        assert_eq!(environment.color_at(Vector::new(0.0, -2.0, 1.0)), colors[5]);
+    }
+}
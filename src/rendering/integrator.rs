@@ -0,0 +1,256 @@
+use crate::math::Rng;
+
+use super::{
+    bsdf::Bsdf,
+    intersection::{Computations, Intersection},
+    world::World,
+    Color, Ray,
+};
+
+// bounces after which paths start being randomly cut off
+const ROULETTE_START: u32 = 3;
+
+// how the light arriving along a camera ray is estimated
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Integrator {
+    // direct phong lighting with recursive mirror and glass rays, see `World::color_at`
+    Whitted,
+    // unidirectional path tracing, which picks up indirect light and color bleeding.
+    // diffuse surfaces reflect `albedo / pi` of the light reaching them, so lights
+    // end up dimmer than under phong shading
+    PathTracer { max_bounces: u32 },
+}
+
+impl Integrator {
+    pub fn radiance(&self, world: &World, ray: &Ray, rng: &mut Rng) -> Color {
+        match self {
+            Self::Whitted => world.color_at(ray, rng),
+            Self::PathTracer { max_bounces } => path_trace(world, ray, *max_bounces, rng),
+        }
+    }
+}
+
+fn path_trace(world: &World, ray: &Ray, max_bounces: u32, rng: &mut Rng) -> Color {
+    let mut ray = *ray;
+    let mut radiance = Color::new(0.0, 0.0, 0.0);
+    let mut throughput = Color::new(1.0, 1.0, 1.0);
+    // the density the current ray was picked with, so emitters it hits can be weighted against
+    // having already been sampled directly from the previous hit. zero after specular bounces
+    let mut bsdf_pdf = 0.0;
+    let mut origin = ray.origin;
+
+    for bounce in 0.. {
+        let intersections = world.intersect(&ray);
+        let comps = match Intersection::hit(&intersections) {
+            Some(hit) => hit.prepare(&ray, &intersections),
+            None => {
+                radiance = radiance + throughput * world.background(&ray);
+                break;
+            }
+        };
+
+        let material = &comps.object.material;
+        if material.emission != Color::new(0.0, 0.0, 0.0) {
+            let weight = match bsdf_pdf > 0.0 {
+                false => 1.0,
+                true => {
+                    let light_pdf = world
+                        .lights
+                        .iter()
+                        .map(|light| {
+                            light.pdf(world, origin, ray.shutter_time, comps.object, comps.point)
+                        })
+                        .sum();
+                    power_heuristic(bsdf_pdf, light_pdf)
+                }
+            };
+
+            radiance = radiance + throughput * material.emission * weight;
+        }
+
+        if bounce == max_bounces {
+            break;
+        }
+
+        let color = comps.object.color_at(comps.over_point, ray.shutter_time);
+        let bsdf = material.bsdf(color, comps.n1, comps.n2, rng);
+
+        if !bsdf.is_specular() {
+            radiance = radiance + throughput * direct_light(world, &comps, &bsdf, rng);
+        }
+
+        let sample = match bsdf.sample(comps.eye, comps.normal, rng) {
+            Some(sample) => sample,
+            None => break,
+        };
+        throughput = throughput * sample.weight;
+        bsdf_pdf = sample.pdf;
+
+        // refracted rays continue from under the surface, everything else from above it
+        origin = match sample.direction.dot(comps.normal) < 0.0 {
+            true => comps.under_point,
+            false => comps.over_point,
+        };
+        ray = Ray::point_vector(origin, sample.direction).with_shutter_time(ray.shutter_time);
+
+        // randomly end paths that can't contribute much, boosting the survivors to make up for it
+        if bounce >= ROULETTE_START {
+            let survival = throughput
+                .rgb
+                .iter()
+                .fold(0.0_f32, |a, b| a.max(*b))
+                .min(0.95);
+
+            if rng.next_f32() >= survival {
+                break;
+            }
+            throughput = throughput * (1.0 / survival);
+        }
+    }
+
+    radiance
+}
+
+// next event estimation, light reaching the point straight from each light and scattered
+// towards the eye, weighted against how likely the bsdf would have been to find it as well
+fn direct_light(world: &World, comps: &Computations, bsdf: &dyn Bsdf, rng: &mut Rng) -> Color {
+    world
+        .lights
+        .iter()
+        .fold(Color::new(0.0, 0.0, 0.0), |acc, light| {
+            let samples = light.visible_samples(world, comps.over_point, comps.shutter_time, rng);
+
+            let total = samples
+                .iter()
+                .fold(Color::new(0.0, 0.0, 0.0), |acc, sample| {
+                    let cos = sample.direction.dot(comps.normal);
+                    if cos <= 0.0 {
+                        return acc;
+                    }
+
+                    // lights with a surface could have been found by the bounce as well
+                    let weight = match sample.pdf > 0.0 {
+                        true => power_heuristic(
+                            sample.pdf,
+                            bsdf.pdf(comps.eye, sample.direction, comps.normal),
+                        ),
+                        false => 1.0,
+                    };
+
+                    acc + sample.intensity
+                        * sample.transmittance
+                        * bsdf.eval(comps.eye, sample.direction, comps.normal)
+                        * (cos * weight)
+                });
+
+            acc + total * (1.0 / samples.len().max(1) as f32)
+        })
+}
+
+// weighs a sample by how likely it was to be found this way rather than the other
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+
+    match a + b > 0.0 {
+        true => a / (a + b),
+        false => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{Matrix4x4, Point};
+    use crate::rendering::{object_light::ObjectLight, Material, Object, PointLight};
+
+    fn sphere(material: Material, transform: Matrix4x4) -> Object {
+        let mut object = Object::new_sphere(material, transform);
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+        object
+    }
+
+    fn average(world: &World, ray: &Ray, samples: u32) -> Color {
+        let integrator = Integrator::PathTracer { max_bounces: 8 };
+        let mut rng = Rng::new(7);
+
+        (0..samples).fold(Color::new(0.0, 0.0, 0.0), |acc, _| {
+            acc + integrator.radiance(world, ray, &mut rng)
+        }) * (1.0 / samples as f32)
+    }
+
+    #[test]
+    fn diffuse_sphere_in_a_glowing_room() {
+        // every bounce off the convex sphere lands on the walls, so it reflects exactly its albedo
+        let mut world = World::new();
+        let mut walls = Material::new(Color::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0, 1.0);
+        walls.emission = Color::new(1.0, 1.0, 1.0);
+        world
+            .objects
+            .push(sphere(walls, Matrix4x4::identity().scale(10.0, 10.0, 10.0)));
+        world.objects.push(sphere(
+            Material::new(Color::new(0.5, 0.25, 1.0), 0.1, 0.8, 0.0, 1.0),
+            Matrix4x4::identity(),
+        ));
+
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+        let color = average(&world, &ray, 16);
+
+        assert_eq!(color.round(0.0001), Color::new(0.4, 0.2, 0.8).round(0.0001));
+    }
+
+    #[test]
+    fn light_sampling_agrees_with_bsdf_sampling() {
+        let mut emitter = sphere(Material::default(), {
+            Matrix4x4::identity()
+                .translate(1.5, 0.0, -2.5)
+                .scale(0.5, 0.5, 0.5)
+        });
+        emitter.material.emission = Color::new(4.0, 4.0, 4.0);
+
+        for material in [
+            Material::default(),
+            Material::new_microfacet(Color::new(1.0, 1.0, 1.0), 0.0, 0.4),
+            Material::new_microfacet(Color::new(1.0, 1.0, 1.0), 1.0, 0.3),
+        ] {
+            let mut world = World::new();
+            world.objects.push(sphere(material, Matrix4x4::identity()));
+            world.objects.push(emitter.clone());
+            let emitter = world.objects.len() - 1;
+
+            let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+            let unsampled = average(&world, &ray, 40000).rgb[0];
+
+            // with the emitter registered as a light both strategies are blended by mis
+            world.lights.push(Box::new(ObjectLight::new(emitter, 1)));
+            let sampled = average(&world, &ray, 4000).rgb[0];
+
+            assert!(unsampled > 0.0);
+            assert!(
+                (sampled - unsampled).abs() < unsampled * 0.1,
+                "{} vs {}",
+                sampled,
+                unsampled
+            );
+        }
+    }
+
+    #[test]
+    fn whitted_matches_world() {
+        let mut world = World::new();
+        world.lights.push(Box::new(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )));
+        world
+            .objects
+            .push(sphere(Material::default(), Matrix4x4::identity()));
+
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(
+            Integrator::Whitted.radiance(&world, &ray, &mut Rng::new(0)),
+            world.color_at(&ray, &mut Rng::new(0))
+        );
+    }
+}
@@ -1,13 +1,231 @@
-use super::object::Object;
+use crate::math::{util::EPSILON, Point, Vector};
+
+use super::{bsdf::Dielectric, object::Object, Ray};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
-pub struct Intersection {
+pub struct Intersection<'a> {
+    pub time: f32,
+    pub object: &'a Object,
+}
+
+// precomputed state about a hit that is needed for shading it
+#[derive(Clone, Copy, Debug)]
+pub struct Computations<'a> {
     pub time: f32,
-    pub object: Object,
+    pub object: &'a Object,
+    // the time the ray was cast at, which secondary rays are cast at as well
+    pub shutter_time: f32,
+    pub point: Point,
+    pub over_point: Point,
+    pub under_point: Point,
+    pub eye: Vector,
+    pub normal: Vector,
+    pub reflect: Vector,
+    pub inside: bool,
+    // refractive indices on the side the ray comes from and the side it enters
+    pub n1: f32,
+    pub n2: f32,
 }
 
-impl Intersection {
-    pub fn new(time: f32, object: Object) -> Intersection {
+impl Computations<'_> {
+    // schlick's approximation of the fresnel reflectance at the hit
+    pub fn schlick(&self) -> f32 {
+        Dielectric::new(self.n1, self.n2).reflectance(self.eye.dot(self.normal))
+    }
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(time: f32, object: &'a Object) -> Self {
         Self { time, object }
     }
+
+    // the visible intersection is the one with the lowest non-negative time
+    pub fn hit<'b>(intersections: &'b [Intersection<'a>]) -> Option<&'b Intersection<'a>> {
+        intersections
+            .iter()
+            .filter(|x| x.time >= 0.0)
+            .min_by(|a, b| a.time.total_cmp(&b.time))
+    }
+
+    // `intersections` is the sorted list this hit came from, it is walked to find
+    // which objects contain the hit so refraction knows what it is leaving
+    pub fn prepare(&self, ray: &Ray, intersections: &[Intersection<'a>]) -> Computations<'a> {
+        let point = ray.position_at(self.time);
+        let eye = ray.direction.negate();
+        let mut normal = self.object.normal_at(point, ray.shutter_time);
+        let inside = normal.dot(eye) < 0.0;
+
+        if inside {
+            normal = normal.negate();
+        }
+
+        let (n1, n2) = self.refractive_indices(intersections);
+
+        Computations {
+            time: self.time,
+            object: self.object,
+            shutter_time: ray.shutter_time,
+            point,
+            over_point: normal * EPSILON + point,
+            under_point: point - normal * EPSILON,
+            eye,
+            normal,
+            reflect: ray.direction.reflect(normal),
+            inside,
+            n1,
+            n2,
+        }
+    }
+
+    fn refractive_indices(&self, intersections: &[Intersection<'a>]) -> (f32, f32) {
+        let mut containers: Vec<&Object> = Vec::new();
+        let index = |containers: &Vec<&Object>| {
+            containers
+                .last()
+                .map_or(1.0, |object| object.material.refractive_index)
+        };
+
+        for intersection in intersections {
+            // the same object at the same distance, not just an equal one
+            let is_hit =
+                std::ptr::eq(intersection.object, self.object) && intersection.time == self.time;
+
+            let n1 = index(&containers);
+
+            match containers
+                .iter()
+                .position(|object| std::ptr::eq(*object, intersection.object))
+            {
+                Some(position) => {
+                    containers.remove(position);
+                }
+                None => containers.push(intersection.object),
+            }
+
+            if is_hit {
+                return (n1, index(&containers));
+            }
+        }
+
+        (1.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::{util::about_equals, Matrix4x4};
+    use crate::rendering::Material;
+
+    fn sphere() -> Object {
+        let mut sphere = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        sphere.calc_inverse_transform();
+        sphere.calc_inverse_transpose_transform();
+        sphere
+    }
+
+    #[test]
+    fn hit_skips_negative_times() {
+        let sphere = sphere();
+        let intersections = [
+            Intersection::new(5.0, &sphere),
+            Intersection::new(-3.0, &sphere),
+            Intersection::new(2.0, &sphere),
+        ];
+
+        assert_eq!(Intersection::hit(&intersections).unwrap().time, 2.0);
+        assert!(Intersection::hit(&intersections[1..2]).is_none());
+    }
+
+    #[test]
+    fn prepare_inside() {
+        let sphere = sphere();
+        let ray = Ray::new(0.0, 0.0, 0.0, 0.0, 0.0, 1.0);
+        let hit = Intersection::new(1.0, &sphere);
+        let comps = hit.prepare(&ray, &[hit]);
+
+        assert!(comps.inside);
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.normal, Vector::new(0.0, 0.0, -1.0));
+        assert!(comps.over_point.xyz[2] < comps.point.xyz[2]);
+        assert!(comps.under_point.xyz[2] > comps.point.xyz[2]);
+    }
+
+    #[test]
+    fn schlick() {
+        let mut glass = sphere();
+        glass.material.transparency = 1.0;
+        glass.material.refractive_index = 1.5;
+
+        let total_internal = Ray::new(0.0, 0.0, 2.0_f32.sqrt() / 2.0, 0.0, 1.0, 0.0);
+        let intersections = [
+            Intersection::new(-(2.0_f32.sqrt()) / 2.0, &glass),
+            Intersection::new(2.0_f32.sqrt() / 2.0, &glass),
+        ];
+        let comps = intersections[1].prepare(&total_internal, &intersections);
+        assert_eq!(comps.schlick(), 1.0);
+
+        let perpendicular = Ray::new(0.0, 0.0, 0.0, 0.0, 1.0, 0.0);
+        let intersections = [
+            Intersection::new(-1.0, &glass),
+            Intersection::new(1.0, &glass),
+        ];
+        let comps = intersections[1].prepare(&perpendicular, &intersections);
+        assert!(about_equals(comps.schlick(), 0.04, 0.0001));
+
+        let grazing = Ray::new(0.0, 0.99, -2.0, 0.0, 0.0, 1.0);
+        let intersections = [Intersection::new(1.8589, &glass)];
+        let comps = intersections[0].prepare(&grazing, &intersections);
+        assert!(about_equals(comps.schlick(), 0.48873, 0.001));
+    }
+
+    #[test]
+    fn refractive_indices() {
+        let glass = |transform: Matrix4x4, refractive_index: f32| {
+            let mut object = Object::new_sphere(Material::default(), transform);
+            object.material.transparency = 1.0;
+            object.material.refractive_index = refractive_index;
+            object.calc_inverse_transform();
+            object.calc_inverse_transpose_transform();
+            object
+        };
+
+        let a = glass(Matrix4x4::identity().scale(2.0, 2.0, 2.0), 1.5);
+        let b = glass(Matrix4x4::identity().translate(0.0, 0.0, -0.25), 2.0);
+        let c = glass(Matrix4x4::identity().translate(0.0, 0.0, 0.25), 2.5);
+
+        let ray = Ray::new(0.0, 0.0, -4.0, 0.0, 0.0, 1.0);
+        let intersections = [
+            Intersection::new(2.0, &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6.0, &a),
+        ];
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+
+        for (hit, (n1, n2)) in intersections.iter().zip(expected) {
+            let comps = hit.prepare(&ray, &intersections);
+            assert_eq!((comps.n1, comps.n2), (n1, n2));
+        }
+
+        // identical copies at the same distance are still told apart
+        let d = a.clone();
+        let intersections = [
+            Intersection::new(2.0, &a),
+            Intersection::new(2.0, &d),
+            Intersection::new(6.0, &a),
+            Intersection::new(6.0, &d),
+        ];
+        let comps = intersections[1].prepare(&ray, &intersections);
+        assert_eq!((comps.n1, comps.n2), (1.5, 1.5));
+    }
 }
@@ -0,0 +1,92 @@
+use crate::math::{Point, Rng, Vector};
+
+use super::{attenuation::Attenuation, world::World, Color, Object};
+
+// anything that can illuminate a point, new kinds of lights only need to say
+// how they are sampled from a point and the shading code picks them up as is
+pub trait Light: Send + Sync {
+    // the unattenuated intensity, which is what the ambient term uses
+    fn intensity(&self) -> Color;
+
+    // unshadowed samples of the light as seen from the point at the given ray time.
+    // stochastic lights draw from the rng, so every call can give a fresh set of samples
+    fn samples(&self, world: &World, point: Point, time: f32, rng: &mut Rng) -> Vec<LightSample>;
+
+    // samples with a shadow ray cast towards each of them at the given ray time
+    fn visible_samples(
+        &self,
+        world: &World,
+        point: Point,
+        time: f32,
+        rng: &mut Rng,
+    ) -> Vec<LightSample> {
+        let mut samples = self.samples(world, point, time, rng);
+
+        for sample in &mut samples {
+            // no need to cast a shadow ray towards a light that doesn't shine here
+            if sample.intensity != Color::new(0.0, 0.0, 0.0) {
+                sample.transmittance =
+                    world.shadow_transmittance(point, sample.direction, sample.distance, time);
+            }
+        }
+
+        samples
+    }
+
+    // the density per unit solid angle with which `samples` picks the direction from
+    // `point` to `position` on `object`. lights that rays can't hit never pick it
+    fn pdf(
+        &self,
+        _world: &World,
+        _point: Point,
+        _time: f32,
+        _object: &Object,
+        _position: Point,
+    ) -> f32 {
+        0.0
+    }
+}
+
+// a direction towards a light along with how much of it reaches the shaded point
+#[derive(Copy, Clone, Debug)]
+pub struct LightSample {
+    // normalized, pointing from the shaded point to the light
+    pub direction: Vector,
+    // how far shadow rays have to go, infinite for directional lights
+    pub distance: f32,
+    // what the light emits towards the point, before shadowing
+    pub intensity: Color,
+    pub transmittance: Color,
+    // the density the direction was picked with, zero for lights that are a single point
+    pub pdf: f32,
+}
+
+impl LightSample {
+    pub fn new(direction: Vector, distance: f32, intensity: Color, transmittance: Color) -> Self {
+        Self {
+            direction,
+            distance,
+            intensity,
+            transmittance,
+            pdf: 0.0,
+        }
+    }
+
+    // an unshadowed sample of a light at a position
+    pub fn towards(
+        point: Point,
+        position: Point,
+        intensity: Color,
+        attenuation: Attenuation,
+    ) -> Self {
+        let vector = position - point;
+        let distance = vector.magnitude();
+
+        Self::new(
+            vector / distance,
+            distance,
+            intensity * attenuation.factor(distance),
+            Color::new(1.0, 1.0, 1.0),
+        )
+    }
+}
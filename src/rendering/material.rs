@@ -1,22 +1,81 @@
-use super::Color;
+use crate::math::Rng;
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+use super::{
+    bsdf::{Dielectric, Lambertian, MicrofacetBsdf, Mirror, Phong, SurfaceBsdf},
+    microfacet::Microfacet,
+    Color, Pattern,
+};
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Material {
     pub color: Color,
+    pub pattern: Option<Pattern>,
     pub ambient: f32,
     pub diffuse: f32,
     pub specular: f32,
     pub shininess: f32,
+    pub reflective: f32,
+    pub transparency: f32,
+    pub refractive_index: f32,
+    // light given off by the surface itself, independent of any lights
+    pub emission: Color,
+    // a metallic/roughness model that takes over from the diffuse and specular terms
+    pub microfacet: Option<Microfacet>,
 }
 
 impl Material {
     pub fn new(color: Color, ambient: f32, diffuse: f32, specular: f32, shininess: f32) -> Self {
         Self {
             color,
+            pattern: None,
             ambient,
             diffuse,
             specular,
             shininess,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            emission: Color::new(0.0, 0.0, 0.0),
+            microfacet: None,
+        }
+    }
+
+    // a physically based material, `color` being its base color
+    pub fn new_microfacet(color: Color, metallic: f32, roughness: f32) -> Self {
+        Self {
+            microfacet: Some(Microfacet::new(metallic, roughness)),
+            ..Self::new(color, 0.1, 0.0, 0.0, 1.0)
+        }
+    }
+
+    // the bsdf of one of the material's lobes at a point with `color`, each picked as often as it
+    // contributes. `n1` and `n2` are the refractive indices on the eye's side and the other
+    pub fn bsdf(&self, color: Color, n1: f32, n2: f32, rng: &mut Rng) -> SurfaceBsdf {
+        let transparency = self.transparency.clamp(0.0, 1.0);
+        let reflective = self.reflective.clamp(0.0, 1.0 - transparency);
+        let choice = rng.next_f32();
+
+        if choice < transparency {
+            SurfaceBsdf::Dielectric(Dielectric::new(n1, n2))
+        } else if choice < transparency + reflective {
+            SurfaceBsdf::Mirror(Mirror)
+        } else {
+            self.surface_bsdf(color)
+        }
+    }
+
+    // the lobe that isn't a mirror or glass, which is what lights reflect off directly
+    pub fn surface_bsdf(&self, color: Color) -> SurfaceBsdf {
+        if let Some(microfacet) = self.microfacet {
+            SurfaceBsdf::Microfacet(MicrofacetBsdf::new(microfacet, color))
+        } else if self.specular > 0.0 {
+            SurfaceBsdf::Phong(Phong::new(
+                color * self.diffuse,
+                self.specular,
+                self.shininess,
+            ))
+        } else {
+            SurfaceBsdf::Lambertian(Lambertian::new(color * self.diffuse))
         }
     }
 
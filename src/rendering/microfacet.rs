@@ -0,0 +1,208 @@
+use std::f32::consts::PI;
+
+use crate::math::{
+    sampling::{around, cosine_hemisphere},
+    Rng, Vector,
+};
+
+use super::Color;
+
+// a metallic/roughness surface as authored by most modelling tools, made of tiny mirrors
+// spread by the ggx (trowbridge-reitz) distribution, shadowed by the smith term and
+// reflecting by schlick's fresnel. the base color is the material's color or pattern
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Microfacet {
+    // 0 for dielectrics, 1 for metals, which tint their reflections and have no diffuse
+    pub metallic: f32,
+    // perceptual roughness, squared to get the width of the distribution
+    pub roughness: f32,
+    // scales the reflectance of dielectrics head-on, 0.5 being the usual 4%
+    pub specular: f32,
+}
+
+impl Microfacet {
+    pub fn new(metallic: f32, roughness: f32) -> Self {
+        Self {
+            metallic,
+            roughness,
+            specular: 0.5,
+        }
+    }
+
+    // the light reflected towards `eye` per unit of light arriving from `light`, not counting
+    // the cosine at the light's side. directions point away from the surface
+    pub fn eval(&self, base: Color, eye: Vector, light: Vector, normal: Vector) -> Color {
+        let (n_dot_v, n_dot_l) = (eye.dot(normal), light.dot(normal));
+        if n_dot_v <= 0.0 || n_dot_l <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let half = (eye + light).normalize();
+        let alpha = self.alpha();
+        let fresnel = schlick(self.reflectance(base), eye.dot(half));
+        let specular = ggx(normal.dot(half), alpha) * smith(n_dot_v, alpha) * smith(n_dot_l, alpha)
+            / (4.0 * n_dot_v * n_dot_l);
+
+        // whatever the facets reflect never reaches the diffuse layer underneath
+        let diffuse = base * ((1.0 - self.metallic) / PI);
+        diffuse * (Color::new(1.0, 1.0, 1.0) - fresnel) + fresnel * specular
+    }
+
+    // a direction for light to arrive from, picked from either the facets or the diffuse layer
+    pub fn sample(&self, eye: Vector, normal: Vector, rng: &mut Rng) -> Vector {
+        if rng.next_f32() >= self.specular_chance() {
+            return cosine_hemisphere(normal, rng);
+        }
+
+        // a facet normal with a density of ggx * cos, mirrored around
+        let alpha2 = self.alpha().powi(2);
+        let v = rng.next_f32();
+        let cos = ((1.0 - v) / (1.0 + (alpha2 - 1.0) * v)).sqrt();
+        let half = around(normal, cos, 2.0 * PI * rng.next_f32());
+
+        eye.negate().reflect(half)
+    }
+
+    // the density `sample` picks `light` with, per unit solid angle
+    pub fn pdf(&self, eye: Vector, light: Vector, normal: Vector) -> f32 {
+        let n_dot_l = light.dot(normal);
+        if eye.dot(normal) <= 0.0 || n_dot_l <= 0.0 {
+            return 0.0;
+        }
+
+        let half = (eye + light).normalize();
+        let n_dot_h = normal.dot(half);
+        // the jacobian from facet normals to the mirrored directions
+        let specular = ggx(n_dot_h, self.alpha()) * n_dot_h / (4.0 * eye.dot(half));
+        let chance = self.specular_chance();
+
+        chance * specular + (1.0 - chance) * n_dot_l / PI
+    }
+
+    // perfectly smooth facets would need a dirac delta, so they're kept barely rough
+    fn alpha(&self) -> f32 {
+        (self.roughness * self.roughness).max(0.002)
+    }
+
+    // the reflectance head-on, grey for dielectrics and the base color for metals
+    fn reflectance(&self, base: Color) -> Color {
+        let dielectric = 0.08 * self.specular;
+        Color::new(dielectric, dielectric, dielectric) * (1.0 - self.metallic)
+            + base * self.metallic
+    }
+
+    // how often `sample` picks the facets, always for metals and half the time for dielectrics
+    fn specular_chance(&self) -> f32 {
+        0.5 + 0.5 * self.metallic.clamp(0.0, 1.0)
+    }
+}
+
+// the share of facets facing along the half vector
+fn ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * d * d)
+}
+
+// the share of facets visible from a direction, not hidden behind their neighbours
+fn smith(n_dot_v: f32, alpha: f32) -> f32 {
+    let alpha2 = alpha * alpha;
+    2.0 * n_dot_v / (n_dot_v + (alpha2 + (1.0 - alpha2) * n_dot_v * n_dot_v).sqrt())
+}
+
+fn schlick(reflectance: Color, cos: f32) -> Color {
+    let factor = (1.0 - cos).clamp(0.0, 1.0).powi(5);
+    reflectance + (Color::new(1.0, 1.0, 1.0) - reflectance) * factor
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::sampling::uniform_hemisphere;
+
+    // the fraction of light arriving from all over the hemisphere that's reflected towards `eye`
+    fn albedo(microfacet: &Microfacet, base: Color, eye: Vector, samples: u32) -> f32 {
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let mut rng = Rng::new(3);
+
+        (0..samples)
+            .map(|_| {
+                let light = microfacet.sample(eye, normal, &mut rng);
+                let pdf = microfacet.pdf(eye, light, normal);
+
+                match pdf > 0.0 {
+                    true => {
+                        microfacet.eval(base, eye, light, normal).rgb[0] * light.dot(normal) / pdf
+                    }
+                    false => 0.0,
+                }
+            })
+            .sum::<f32>()
+            / samples as f32
+    }
+
+    #[test]
+    fn reflectance_at_normal_incidence() {
+        let base = Color::new(0.9, 0.6, 0.2);
+
+        assert_eq!(
+            Microfacet::new(0.0, 0.5).reflectance(base).round(0.0001),
+            Color::new(0.04, 0.04, 0.04)
+        );
+        assert_eq!(Microfacet::new(1.0, 0.5).reflectance(base), base);
+    }
+
+    #[test]
+    fn nothing_below_the_surface() {
+        let microfacet = Microfacet::new(0.5, 0.5);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let eye = Vector::new(0.0, 1.0, 0.0);
+        let below = Vector::new(0.0, -1.0, 0.0);
+
+        assert_eq!(
+            microfacet.eval(Color::new(1.0, 1.0, 1.0), eye, below, normal),
+            Color::new(0.0, 0.0, 0.0)
+        );
+        assert_eq!(microfacet.pdf(eye, below, normal), 0.0);
+    }
+
+    #[test]
+    fn conserves_energy() {
+        let white = Color::new(1.0, 1.0, 1.0);
+        let eye = Vector::new(0.6, 0.8, 0.0);
+
+        for roughness in [0.1, 0.5, 1.0] {
+            let metal = albedo(&Microfacet::new(1.0, roughness), white, eye, 20000);
+            assert!(metal <= 1.01, "{} at {}", metal, roughness);
+            // light bouncing between facets isn't followed, which only rough metals lose much to
+            if roughness <= 0.5 {
+                assert!(metal > 0.85, "{} at {}", metal, roughness);
+            }
+
+            let dielectric = albedo(&Microfacet::new(0.0, roughness), white, eye, 20000);
+            assert!(dielectric <= 1.01, "{} at {}", dielectric, roughness);
+        }
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        let microfacet = Microfacet::new(0.3, 0.4);
+        let normal = Vector::new(0.0, 1.0, 0.0);
+        let eye = Vector::new(0.0, 0.8, -0.6);
+        let mut rng = Rng::new(5);
+
+        // uniform directions over the hemisphere, each covering 2 pi / n of it
+        let samples = 100000;
+        let total = (0..samples)
+            .map(|_| {
+                let light = uniform_hemisphere(normal, &mut rng);
+                microfacet.pdf(eye, light, normal)
+            })
+            .sum::<f32>()
+            * 2.0
+            * PI
+            / samples as f32;
+
+        assert!((total - 1.0).abs() < 0.05, "{}", total);
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::math::Point;
+
+use super::triangle::Triangle;
+
+// reads the triangles of a wavefront obj file, see `parse_obj`
+pub fn load_obj<P: AsRef<Path>>(path: P) -> io::Result<Vec<Triangle>> {
+    parse_obj(&fs::read_to_string(path)?)
+}
+
+// the vertices and faces of an obj file, with polygons split into fans of triangles.
+// texture coordinates, normals, groups and materials are skipped
+pub fn parse_obj(text: &str) -> io::Result<Vec<Triangle>> {
+    let mut vertices = Vec::new();
+    let mut triangles = Vec::new();
+
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("v") => {
+                let mut xyz = [0.0; 3];
+                for value in &mut xyz {
+                    *value = tokens
+                        .next()
+                        .and_then(|token| token.parse().ok())
+                        .ok_or_else(|| invalid_data("invalid obj vertex"))?;
+                }
+                vertices.push(Point::new_arr(xyz));
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| vertex_index(token, vertices.len()))
+                    .collect::<io::Result<Vec<usize>>>()?;
+
+                if face.len() < 3 {
+                    return Err(invalid_data("obj face with fewer than 3 vertices"));
+                }
+
+                for i in 1..face.len() - 1 {
+                    triangles.push(Triangle::new(
+                        vertices[face[0]],
+                        vertices[face[i]],
+                        vertices[face[i + 1]],
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(triangles)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// the vertex of a face entry like `3`, `3/1` or `3/1/2`, counting from 1, or from the
+// end of the vertices read so far when negative
+fn vertex_index(token: &str, count: usize) -> io::Result<usize> {
+    let index: i64 = token
+        .split('/')
+        .next()
+        .and_then(|index| index.parse().ok())
+        .ok_or_else(|| invalid_data("invalid obj face"))?;
+
+    let index = match index < 0 {
+        true => count as i64 + index,
+        false => index - 1,
+    };
+
+    match (0..count as i64).contains(&index) {
+        true => Ok(index as usize),
+        false => Err(invalid_data("obj face refers to a missing vertex")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_faces() {
+        let obj = "# a square and a triangle\n\
+            v -1 1 0\n\
+            v -1 0 0\n\
+            v 1 0 0\n\
+            v 1 1 0\n\
+            vn 0 0 1\n\
+            f 1 2 3 4\n\
+            f 1/1/1 3//1 -1\n";
+        let triangles = parse_obj(obj).unwrap();
+
+        assert_eq!(triangles.len(), 3);
+        // the square is split into a fan around its first vertex
+        assert_eq!(triangles[0].p1, Point::new(-1.0, 1.0, 0.0));
+        assert_eq!(triangles[0].p3, Point::new(1.0, 0.0, 0.0));
+        assert_eq!(triangles[1].p2, Point::new(1.0, 0.0, 0.0));
+        assert_eq!(triangles[1].p3, Point::new(1.0, 1.0, 0.0));
+        assert_eq!(triangles[2].p3, Point::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn rejects_broken_files() {
+        assert!(parse_obj("v 1 2\n").is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n").is_err());
+        assert!(parse_obj("v 0 0 0\nf 0 1 1\n").is_err());
+    }
+}
@@ -1,19 +1,33 @@
-use crate::math::{Matrix4x4, Point, Vector};
+use crate::math::{Bounds, Decomposition, Matrix4x4, Point, Vector};
 
-use super::Material;
+use super::{triangle::Triangle, Color, Material};
+
+// how many times along its motion a moving object's box is taken
+const MOTION_BOUNDS_STEPS: u32 = 16;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Shape {
     Sphere,
+    Triangle(Triangle),
 }
 
+// a moving object's transforms at times 0 and 1, decomposed once so each ray only blends them
 #[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Motion {
+    pub start: Decomposition,
+    pub end: Decomposition,
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct Object {
     pub shape: Shape,
     pub material: Material,
     pub transform: Matrix4x4,
     pub inverse_transform: Matrix4x4,
     pub inverse_transpose_transform: Matrix4x4,
+    pub casts_shadow: bool,
+    // set by `set_end_transform` for objects that move during the exposure
+    pub motion: Option<Motion>,
 }
 
 impl Object {
@@ -24,12 +38,89 @@ impl Object {
             transform,
             inverse_transform: Matrix4x4::zero(),
             inverse_transpose_transform: Matrix4x4::zero(),
+            casts_shadow: true,
+            motion: None,
         }
     }
 
-    pub fn normal_at(&self, point: Point) -> Vector {
-        match self.shape {
-            Shape::Sphere => self.sphere_normal(point),
+    pub fn new_triangle(material: Material, triangle: Triangle, transform: Matrix4x4) -> Self {
+        Self {
+            shape: Shape::Triangle(triangle),
+            ..Self::new_sphere(material, transform)
+        }
+    }
+
+    pub fn normal_at(&self, point: Point, time: f32) -> Vector {
+        let inverse_transform = self
+            .inverse_transform_at(time)
+            .expect("a flattened object is never hit");
+        let object_point = point * inverse_transform;
+
+        let object_normal = match self.shape {
+            Shape::Sphere => object_point - Point::zero(),
+            Shape::Triangle(triangle) => triangle.normal,
+        };
+
+        let inverse_transpose_transform = match self.motion {
+            Some(_) => inverse_transform.transpose(),
+            None => self.inverse_transpose_transform,
+        };
+
+        (object_normal * inverse_transpose_transform).normalize()
+    }
+
+    // the world space box the object fits in, over its whole motion for moving objects
+    pub fn bounds(&self) -> Bounds {
+        let local = match self.shape {
+            Shape::Sphere => Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0)),
+            Shape::Triangle(triangle) => triangle.bounds(),
+        };
+
+        match self.motion {
+            // rotations sweep along arcs, so the box is taken at several points along the way
+            Some(_) => (0..=MOTION_BOUNDS_STEPS).fold(Bounds::empty(), |acc, step| {
+                let time = step as f32 / MOTION_BOUNDS_STEPS as f32;
+                acc.union(local.transform(self.transform_at(time)))
+            }),
+            None => local.transform(self.transform),
+        }
+    }
+
+    // the material's color, or its pattern sampled in object space
+    pub fn color_at(&self, point: Point, time: f32) -> Color {
+        match &self.material.pattern {
+            Some(pattern) => {
+                let inverse_transform = self
+                    .inverse_transform_at(time)
+                    .expect("a flattened object is never hit");
+                pattern.color_at(point * inverse_transform)
+            }
+            None => self.material.color,
+        }
+    }
+
+    // makes the object move from `transform` at time 0 to `end` at time 1, so `transform`
+    // has to be set first
+    pub fn set_end_transform(&mut self, end: Matrix4x4) {
+        self.motion = Some(Motion {
+            start: self.transform.decompose(),
+            end: end.decompose(),
+        });
+    }
+
+    pub fn transform_at(&self, time: f32) -> Matrix4x4 {
+        match self.motion {
+            Some(motion) => Matrix4x4::blend(motion.start, motion.end, time.clamp(0.0, 1.0)),
+            None => self.transform,
+        }
+    }
+
+    // static objects use the cached inverse, moving ones have to invert for every ray and
+    // have none while their scale passes through zero
+    pub fn inverse_transform_at(&self, time: f32) -> Option<Matrix4x4> {
+        match self.motion {
+            Some(_) => self.transform_at(time).inverse(),
+            None => Some(self.inverse_transform),
         }
     }
 
@@ -40,19 +131,12 @@ impl Object {
     pub fn calc_inverse_transpose_transform(&mut self) {
         self.inverse_transpose_transform = self.inverse_transform.transpose();
     }
-
-    fn sphere_normal(&self, point: Point) -> Vector {
-        let sphere_point = point * self.inverse_transform;
-        let sphere_normal = sphere_point - Point::zero();
-        let world_normal = sphere_normal * self.inverse_transpose_transform;
-
-        world_normal.normalize()
-    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::rendering::Ray;
 
     #[test]
     fn reflect() {
@@ -72,4 +156,82 @@ mod test {
             Vector::new(1.0, 0.0, 0.0)
         );
     }
+
+    #[test]
+    fn sphere_bounds() {
+        let sphere = Object::new_sphere(
+            Material::default(),
+            Matrix4x4::identity()
+                .translate(1.0, 2.0, 3.0)
+                .scale(2.0, 1.0, 1.0),
+        );
+
+        assert_eq!(
+            sphere.bounds(),
+            Bounds::new(Point::new(-1.0, 1.0, 2.0), Point::new(3.0, 3.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn moving_sphere() {
+        let mut sphere = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        sphere.set_end_transform(Matrix4x4::identity().translate(4.0, 0.0, 0.0));
+        sphere.calc_inverse_transform();
+        sphere.calc_inverse_transpose_transform();
+
+        assert_eq!(
+            sphere.transform_at(0.5),
+            Matrix4x4::identity().translate(2.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            sphere.normal_at(Point::new(2.0, 1.0, 0.0), 0.5),
+            Vector::new(0.0, 1.0, 0.0)
+        );
+        assert_eq!(
+            sphere.bounds(),
+            Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(5.0, 1.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn transformed_triangle() {
+        let triangle = Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        );
+        let mut object = Object::new_triangle(
+            Material::default(),
+            triangle,
+            Matrix4x4::identity().translate(0.0, 0.0, 3.0),
+        );
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+        let ray = Ray::new(0.0, 0.5, -2.0, 0.0, 0.0, 1.0);
+
+        let times: Vec<f32> = ray.intersection(&object).iter().map(|x| x.time).collect();
+        assert_eq!(times, [5.0]);
+        assert_eq!(
+            object.normal_at(Point::new(0.0, 0.5, 3.0), 0.0),
+            Vector::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            object.bounds(),
+            Bounds::new(Point::new(-1.0, 0.0, 3.0), Point::new(1.0, 1.0, 3.0))
+        );
+    }
+
+    #[test]
+    fn flattened_while_moving() {
+        // mirroring along x passes through a zero scale halfway
+        let mut sphere = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        sphere.set_end_transform(Matrix4x4::identity().scale(-1.0, 1.0, 1.0));
+        sphere.calc_inverse_transform();
+        sphere.calc_inverse_transpose_transform();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert!(sphere.inverse_transform_at(0.5).is_none());
+        assert!(ray.with_shutter_time(0.5).intersection(&sphere).is_empty());
+        assert!(!ray.with_shutter_time(0.25).intersection(&sphere).is_empty());
+    }
 }
@@ -0,0 +1,224 @@
+use std::f32::consts::PI;
+
+use crate::math::{sampling, util::EPSILON, Matrix4x4, Point, Rng};
+
+use super::{world::World, Color, Light, LightSample, Object};
+
+// turns an emissive object into a light, sampling random points on its surface
+// so that it lights and shadows the scene like an area light of the same shape.
+// only spheres can be sampled so far, not triangles
+#[derive(Clone)]
+pub struct ObjectLight {
+    // the object is looked up in `world.objects` on every use, so the light
+    // follows any changes made to it after the light was created
+    pub index: usize,
+    pub samples: u32,
+}
+
+impl ObjectLight {
+    pub fn new(index: usize, samples: u32) -> Self {
+        Self { index, samples }
+    }
+
+    // the object's transform and its inverse at the given ray time, or none while a
+    // moving object is flattened to nothing and has no surface to emit from
+    fn transforms_at(object: &Object, time: f32) -> Option<(Matrix4x4, Matrix4x4)> {
+        let inverse_transform = object.inverse_transform_at(time)?;
+        Some((object.transform_at(time), inverse_transform))
+    }
+
+    // a uniformly distributed point on the unit sphere in object space, along
+    // with the area of the world space surface that each sample stands for
+    fn sample_surface(transforms: (Matrix4x4, Matrix4x4), rng: &mut Rng) -> (Point, f32) {
+        let point = sampling::unit_sphere(rng) + Point::zero();
+
+        (point * transforms.0, Self::area_at(transforms, point))
+    }
+
+    // transforming the surface stretches each patch of it by the determinant
+    // scaled by how much the transform shrinks the patch's normal
+    fn area_at((transform, inverse_transform): (Matrix4x4, Matrix4x4), object_point: Point) -> f32 {
+        let normal = (object_point - Point::zero()) * inverse_transform.transpose();
+        4.0 * PI * transform.determinant().abs() * normal.magnitude()
+    }
+}
+
+impl Light for ObjectLight {
+    // the object lights its surroundings through its samples, it doesn't brighten
+    // every surface in the scene through the ambient term as well
+    fn intensity(&self) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    fn samples(&self, world: &World, point: Point, time: f32, rng: &mut Rng) -> Vec<LightSample> {
+        let object = &world.objects[self.index];
+        let emission = object.material.emission;
+        let transforms = match Self::transforms_at(object, time) {
+            Some(transforms) => transforms,
+            None => return Vec::new(),
+        };
+
+        (0..self.samples)
+            .map(|_| {
+                let (position, area) = Self::sample_surface(transforms, rng);
+                let vector = position - point;
+                let distance = vector.magnitude();
+                let direction = vector / distance;
+
+                // the patch's solid angle as seen from the point, zero when it faces away
+                let cos_light = -object.normal_at(position, time).dot(direction);
+                let solid_angle = area * cos_light.max(0.0) / (distance * distance);
+
+                // stop the shadow ray short so it doesn't hit the light's own surface
+                let mut sample = LightSample::new(
+                    direction,
+                    distance - EPSILON,
+                    emission * solid_angle,
+                    Color::new(1.0, 1.0, 1.0),
+                );
+                sample.pdf = match solid_angle > 0.0 {
+                    true => 1.0 / solid_angle,
+                    false => 0.0,
+                };
+                sample
+            })
+            .collect()
+    }
+
+    fn pdf(&self, world: &World, point: Point, time: f32, object: &Object, position: Point) -> f32 {
+        if !std::ptr::eq(object, &world.objects[self.index]) {
+            return 0.0;
+        }
+        let transforms = match Self::transforms_at(object, time) {
+            Some(transforms) => transforms,
+            None => return 0.0,
+        };
+
+        let vector = position - point;
+        let distance = vector.magnitude();
+        let cos_light = -object.normal_at(position, time).dot(vector / distance);
+
+        match cos_light > 0.0 {
+            true => {
+                let area = Self::area_at(transforms, position * transforms.1);
+                distance * distance / (cos_light * area)
+            }
+            false => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::Matrix4x4;
+    use crate::rendering::Material;
+
+    fn panel(transform: Matrix4x4) -> Object {
+        let mut object = Object::new_sphere(Material::default(), transform);
+        object.material.emission = Color::new(1.0, 1.0, 1.0);
+        object.calc_inverse_transform();
+        object.calc_inverse_transpose_transform();
+        object
+    }
+
+    fn world(objects: Vec<Object>) -> World {
+        let mut world = World::new();
+        world.objects = objects;
+        world
+    }
+
+    #[test]
+    fn solid_angle_of_a_sphere() {
+        let world = world(vec![panel(Matrix4x4::identity().translate(0.0, 0.0, 10.0))]);
+        let light = ObjectLight::new(0, 8192);
+        let samples = light.samples(&world, Point::zero(), 0.0, &mut Rng::new(3));
+        let average =
+            samples.iter().map(|x| x.intensity.rgb[0]).sum::<f32>() / samples.len() as f32;
+
+        // a unit sphere 10 units away covers 2 * pi * (1 - cos(asin(0.1))) steradians
+        let expected = 2.0 * PI * (1.0 - (1.0 - 0.01_f32).sqrt());
+        assert!((average - expected).abs() < expected * 0.1);
+    }
+
+    #[test]
+    fn stretched_surfaces_cover_more_area() {
+        let small = panel(Matrix4x4::identity());
+        let large = panel(Matrix4x4::identity().scale(2.0, 2.0, 2.0));
+
+        for (object, area) in [(small, 4.0 * PI), (large, 16.0 * PI)] {
+            let transforms = ObjectLight::transforms_at(&object, 0.0).unwrap();
+            let (_, sampled) = ObjectLight::sample_surface(transforms, &mut Rng::new(0));
+
+            assert!((sampled - area).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn pdf_matches_samples() {
+        // an identical copy of the emitter isn't the emitter
+        let emitter = panel(Matrix4x4::identity().translate(0.0, 0.0, 10.0));
+        let world = world(vec![emitter.clone(), emitter]);
+        let light = ObjectLight::new(0, 16);
+
+        for sample in light.samples(&world, Point::zero(), 0.0, &mut Rng::new(1)) {
+            let position = sample.direction * (sample.distance + EPSILON) + Point::zero();
+            let pdf = light.pdf(&world, Point::zero(), 0.0, &world.objects[0], position);
+
+            assert!((pdf - sample.pdf).abs() <= sample.pdf * 0.01);
+        }
+
+        assert_eq!(
+            light.pdf(
+                &world,
+                Point::zero(),
+                0.0,
+                &world.objects[1],
+                Point::new(0.0, 0.0, 9.0)
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn follows_the_object_in_the_world() {
+        let mut world = world(vec![panel(Matrix4x4::identity().translate(0.0, 0.0, 10.0))]);
+        let light = ObjectLight::new(0, 4);
+
+        world.objects[0].material.emission = Color::new(0.0, 2.0, 0.0);
+        let samples = light.samples(&world, Point::zero(), 0.0, &mut Rng::new(0));
+
+        assert!(samples.iter().all(|x| x.intensity.rgb[0] == 0.0));
+        assert!(samples.iter().any(|x| x.intensity.rgb[1] > 0.0));
+        // the emission doesn't leak into the ambient term
+        assert_eq!(light.intensity(), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn moves_with_the_object() {
+        let mut emitter = panel(Matrix4x4::identity().translate(0.0, 0.0, 10.0));
+        emitter.set_end_transform(Matrix4x4::identity().translate(0.0, 0.0, -10.0));
+        let world = world(vec![emitter]);
+        let light = ObjectLight::new(0, 16);
+
+        for sample in light.samples(&world, Point::zero(), 1.0, &mut Rng::new(2)) {
+            assert!(sample.direction.xyz[2] < 0.0);
+
+            let position = sample.direction * (sample.distance + EPSILON) + Point::zero();
+            let pdf = light.pdf(&world, Point::zero(), 1.0, &world.objects[0], position);
+            assert!((pdf - sample.pdf).abs() <= sample.pdf * 0.01);
+        }
+    }
+
+    #[test]
+    fn lights_the_scene_without_shadowing_itself() {
+        let mut world = world(vec![panel(Matrix4x4::identity().translate(0.0, 0.0, -3.0))]);
+        world.lights.push(Box::new(ObjectLight::new(0, 64)));
+
+        let samples = world.lights[0].visible_samples(&world, Point::zero(), 0.0, &mut Rng::new(0));
+
+        assert!(samples
+            .iter()
+            .any(|x| x.transmittance == Color::new(1.0, 1.0, 1.0) && x.intensity.rgb[0] > 0.0));
+    }
+}
@@ -0,0 +1,192 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::math::Point;
+
+use super::{Canvas, Color};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+// how texel coordinates outside of the image are brought back inside it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Addressing {
+    Wrap,
+    Clamp,
+}
+
+// maps a point in object space onto (u, v) coordinates in [0, 1]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UvMapping {
+    Spherical,
+    Planar,
+    Cylindrical,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Texture {
+    pub canvas: Arc<Canvas>,
+    pub filter: Filter,
+    pub addressing: Addressing,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Pattern {
+    Texture {
+        texture: Texture,
+        mapping: UvMapping,
+    },
+}
+
+impl UvMapping {
+    pub fn map(&self, point: Point) -> (f32, f32) {
+        let [x, y, z] = point.xyz;
+
+        match self {
+            Self::Spherical => {
+                let radius = (x * x + y * y + z * z).sqrt();
+                let theta = x.atan2(z);
+                let phi = (y / radius).clamp(-1.0, 1.0).acos();
+
+                (1.0 - (theta / (2.0 * PI) + 0.5), 1.0 - phi / PI)
+            }
+            Self::Planar => (x.rem_euclid(1.0), z.rem_euclid(1.0)),
+            Self::Cylindrical => {
+                let theta = x.atan2(z);
+
+                (1.0 - (theta / (2.0 * PI) + 0.5), y.rem_euclid(1.0))
+            }
+        }
+    }
+}
+
+impl Texture {
+    pub fn new(canvas: Arc<Canvas>, filter: Filter, addressing: Addressing) -> Self {
+        Self {
+            canvas,
+            filter,
+            addressing,
+        }
+    }
+
+    // v runs from the bottom of the image to the top
+    pub fn sample(&self, u: f32, v: f32) -> Color {
+        let x = u * self.canvas.width as f32;
+        let y = (1.0 - v) * self.canvas.height as f32;
+
+        match self.filter {
+            Filter::Nearest => self.texel(x.floor() as i32, y.floor() as i32),
+            Filter::Bilinear => {
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i32, y0 as i32);
+
+                let top = self.texel(x0, y0) * (1.0 - tx) + self.texel(x0 + 1, y0) * tx;
+                let bottom = self.texel(x0, y0 + 1) * (1.0 - tx) + self.texel(x0 + 1, y0 + 1) * tx;
+
+                top * (1.0 - ty) + bottom * ty
+            }
+        }
+    }
+
+    fn texel(&self, x: i32, y: i32) -> Color {
+        let (x, y) = match self.addressing {
+            Addressing::Wrap => (
+                x.rem_euclid(self.canvas.width),
+                y.rem_euclid(self.canvas.height),
+            ),
+            Addressing::Clamp => (
+                x.clamp(0, self.canvas.width - 1),
+                y.clamp(0, self.canvas.height - 1),
+            ),
+        };
+
+        self.canvas.pixel_at(x, y)
+    }
+}
+
+impl Pattern {
+    pub fn new_texture(texture: Texture, mapping: UvMapping) -> Self {
+        Self::Texture { texture, mapping }
+    }
+
+    // expects a point that has already been transformed into object space
+    pub fn color_at(&self, point: Point) -> Color {
+        match self {
+            Self::Texture { texture, mapping } => {
+                let (u, v) = mapping.map(point);
+                texture.sample(u, v)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkers() -> Arc<Canvas> {
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let mut canvas = Canvas::new(2, 2, black);
+        canvas.write_pixel(0, 0, white);
+        canvas.write_pixel(1, 1, white);
+
+        Arc::new(canvas)
+    }
+
+    #[test]
+    fn spherical_mapping() {
+        let cases = [
+            (Point::new(0.0, 0.0, -1.0), (0.0, 0.5)),
+            (Point::new(1.0, 0.0, 0.0), (0.25, 0.5)),
+            (Point::new(0.0, 0.0, 1.0), (0.5, 0.5)),
+            (Point::new(-1.0, 0.0, 0.0), (0.75, 0.5)),
+            (Point::new(0.0, 1.0, 0.0), (0.5, 1.0)),
+            (Point::new(0.0, -1.0, 0.0), (0.5, 0.0)),
+        ];
+
+        for (point, expected) in cases {
+            assert_eq!(UvMapping::Spherical.map(point), expected);
+        }
+    }
+
+    #[test]
+    fn planar_mapping() {
+        assert_eq!(
+            UvMapping::Planar.map(Point::new(0.25, 0.5, -0.25)),
+            (0.25, 0.75)
+        );
+        assert_eq!(
+            UvMapping::Planar.map(Point::new(1.25, 0.0, 0.5)),
+            (0.25, 0.5)
+        );
+    }
+
+    #[test]
+    fn nearest_sampling() {
+        let texture = Texture::new(checkers(), Filter::Nearest, Addressing::Wrap);
+
+        // v is flipped, so the top left texel is at (0, 1)
+        assert_eq!(texture.sample(0.25, 0.75), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(texture.sample(0.75, 0.75), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(texture.sample(1.25, 0.75), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn bilinear_sampling() {
+        let wrap = Texture::new(checkers(), Filter::Bilinear, Addressing::Wrap);
+        let clamp = Texture::new(checkers(), Filter::Bilinear, Addressing::Clamp);
+
+        assert_eq!(wrap.sample(0.5, 0.5), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(wrap.sample(0.25, 0.75), Color::new(1.0, 1.0, 1.0));
+        // the left edge blends with the right column when wrapping, but not when clamped
+        assert_eq!(wrap.sample(0.0, 0.75), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(clamp.sample(0.0, 0.75), Color::new(1.0, 1.0, 1.0));
+    }
+}
@@ -1,11 +1,12 @@
-use crate::math::Point;
+use crate::math::{Point, Rng};
 
-use super::Color;
+use super::{attenuation::Attenuation, world::World, Color, Light, LightSample};
 
 #[derive(Copy, Clone)]
 pub struct PointLight {
     pub position: Point,
     pub intensity: Color,
+    pub attenuation: Attenuation,
 }
 
 impl PointLight {
@@ -13,6 +14,28 @@ impl PointLight {
         Self {
             position,
             intensity,
+            attenuation: Attenuation::None,
         }
     }
 }
+
+impl Light for PointLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(
+        &self,
+        _world: &World,
+        point: Point,
+        _time: f32,
+        _rng: &mut Rng,
+    ) -> Vec<LightSample> {
+        vec![LightSample::towards(
+            point,
+            self.position,
+            self.intensity,
+            self.attenuation,
+        )]
+    }
+}
@@ -2,12 +2,17 @@ use std::ops::Mul;
 
 use crate::math::{Matrix4x4, Point, Vector};
 
-use super::{intersection::Intersection, object::Object};
+use super::{
+    intersection::Intersection,
+    object::{Object, Shape},
+};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Ray {
     pub origin: Point,
     pub direction: Vector,
+    // when during the exposure the ray was cast, moving objects are placed accordingly
+    pub shutter_time: f32,
 }
 
 impl Ray {
@@ -15,41 +20,80 @@ impl Ray {
         Self {
             origin: Point::new(px, py, pz),
             direction: Vector::new(vx, vy, vz),
+            shutter_time: 0.0,
         }
     }
 
     pub fn point_vector(origin: Point, direction: Vector) -> Self {
-        Self { origin, direction }
+        Self {
+            origin,
+            direction,
+            shutter_time: 0.0,
+        }
+    }
+
+    pub fn with_shutter_time(self, shutter_time: f32) -> Self {
+        Self {
+            shutter_time,
+            ..self
+        }
     }
 
     pub fn position_at(&self, time: f32) -> Point {
         self.direction * time + self.origin
     }
 
-    pub fn intersection(&self, object: &Object) -> Option<[Intersection; 2]> {
-        let inverse_transform = *self * object.inverse_transform;
-        let sphere_to_ray = inverse_transform.origin - Point::zero();
+    // every intersection of the ray with the object, unsorted
+    pub fn intersection<'a>(&self, object: &'a Object) -> Vec<Intersection<'a>> {
+        let mut intersections = Vec::new();
+        self.intersect_into(object, &mut intersections);
+        intersections
+    }
 
-        let a = inverse_transform.direction.dot(inverse_transform.direction);
-        let b = inverse_transform.direction.dot(sphere_to_ray) * 2.0;
-        let c = sphere_to_ray.dot(sphere_to_ray) - 1.0;
+    // adds the intersections with the object to `intersections`, so testing many objects
+    // fills one list rather than allocating one for each
+    pub fn intersect_into<'a>(
+        &self,
+        object: &'a Object,
+        intersections: &mut Vec<Intersection<'a>>,
+    ) {
+        // a moving object flattened to nothing at this time can't be hit
+        let inverse_transform = match object.inverse_transform_at(self.shutter_time) {
+            Some(inverse_transform) => *self * inverse_transform,
+            None => return,
+        };
 
-        let discriminant = b.powi(2) - 4.0 * a * c;
+        match object.shape {
+            Shape::Sphere => {
+                let sphere_to_ray = inverse_transform.origin - Point::zero();
 
-        if discriminant >= 0.0 {
-            let sqrt_discriminant = discriminant.sqrt();
-            let two_a = 2.0 * a;
-            let inv_two_a = 1.0 / two_a;
-            Some([
-                Intersection::new((-b - sqrt_discriminant) * inv_two_a, *object),
-                Intersection::new((-b + sqrt_discriminant) * inv_two_a, *object),
-            ])
-        } else {
-            None
+                let a = inverse_transform.direction.dot(inverse_transform.direction);
+                let b = inverse_transform.direction.dot(sphere_to_ray) * 2.0;
+                let c = sphere_to_ray.dot(sphere_to_ray) - 1.0;
+
+                let discriminant = b.powi(2) - 4.0 * a * c;
+
+                if discriminant >= 0.0 {
+                    let sqrt_discriminant = discriminant.sqrt();
+                    let two_a = 2.0 * a;
+                    let inv_two_a = 1.0 / two_a;
+                    intersections.extend([
+                        Intersection::new((-b - sqrt_discriminant) * inv_two_a, object),
+                        Intersection::new((-b + sqrt_discriminant) * inv_two_a, object),
+                    ]);
+                }
+            }
+            Shape::Triangle(triangle) => {
+                if let Some(time) =
+                    triangle.intersect(inverse_transform.origin, inverse_transform.direction)
+                {
+                    intersections.push(Intersection::new(time, object));
+                }
+            }
         }
     }
 
-    pub fn hit(intersections: &mut [Intersection; 2]) -> Option<Intersection> {
+    pub fn hit<'a>(intersections: &mut [Intersection<'a>; 2]) -> Option<Intersection<'a>> {
         let a = match intersections[0].time > intersections[1].time {
             true => intersections[0],
             false => intersections[1],
@@ -67,5 +111,6 @@ impl Mul<Matrix4x4> for Ray {
     type Output = Self;
     fn mul(self, rhs: Matrix4x4) -> Self {
         Self::point_vector(self.origin * rhs, self.direction * rhs)
+            .with_shutter_time(self.shutter_time)
     }
 }
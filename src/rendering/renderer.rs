@@ -0,0 +1,303 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+
+use crate::math::Rng;
+
+use super::{
+    antialiasing::{PixelFilter, Sampler},
+    integrator::Integrator,
+    Canvas, Color,
+};
+
+// splits the canvas into square tiles that worker threads pull from a shared
+// counter, so threads that get cheap tiles just go on to take more of them
+pub struct Renderer {
+    pub threads: usize,
+    pub tile_size: i32,
+    // how many samples `render_sampled` takes per pixel, and where and how they are weighted
+    pub samples: u32,
+    pub sampler: Sampler,
+    pub filter: PixelFilter,
+    // pixels keep taking batches of `samples` until the standard error of their mean drops
+    // below `variance_threshold` or they have `max_samples`, which by default stops at one batch
+    pub max_samples: u32,
+    pub variance_threshold: f32,
+    // how scenes rendered through a camera are shaded
+    pub integrator: Integrator,
+    // every pixel draws its random numbers from a generator seeded by this and its position,
+    // so the same seed renders the same image whatever the thread count and tile size
+    pub seed: u64,
+}
+
+// a rendered tile, with its pixels stored row by row
+struct Tile {
+    x: i32,
+    y: i32,
+    width: i32,
+    pixels: Vec<Color>,
+}
+
+impl Renderer {
+    pub fn new(threads: usize, tile_size: i32) -> Self {
+        Self {
+            threads: threads.max(1),
+            tile_size: tile_size.max(1),
+            samples: 1,
+            sampler: Sampler::Grid,
+            filter: PixelFilter::Box,
+            max_samples: 0,
+            variance_threshold: 0.01,
+            integrator: Integrator::Whitted,
+            seed: 0,
+        }
+    }
+
+    // one thread per core
+    pub fn default() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, |x| x.get()), 16)
+    }
+
+    // colors every pixel with the filtered average of `shade` at several points around
+    // its center, `shade` gets canvas coordinates and a generator seeded for the pixel.
+    // returns how many times `shade` was called, i.e. how many camera rays were traced
+    pub fn render_sampled<F>(&self, canvas: &mut Canvas, shade: F) -> u64
+    where
+        F: Fn(f32, f32, &mut Rng) -> Color + Sync,
+    {
+        let rays = AtomicU64::new(0);
+
+        self.render(canvas, |i, j| {
+            let mut rng = Rng::for_pixel(self.seed, i, j);
+            let (color, samples) = self.sample_pixel(i, j, &shade, &mut rng);
+
+            rays.fetch_add(samples as u64, Ordering::Relaxed);
+            color
+        });
+
+        rays.into_inner()
+    }
+
+    fn sample_pixel<F>(&self, i: i32, j: i32, shade: &F, rng: &mut Rng) -> (Color, u32)
+    where
+        F: Fn(f32, f32, &mut Rng) -> Color,
+    {
+        let radius = self.filter.radius();
+        let scramble = rng.next_u32();
+
+        let mut total = Color::new(0.0, 0.0, 0.0);
+        let mut total_weight = 0.0;
+        // plain sums of the samples and their squares, for the mean and its variance
+        let mut sum = [0.0; 3];
+        let mut sum_squares = [0.0; 3];
+        let mut taken = 0;
+
+        loop {
+            let points = self
+                .sampler
+                .points(taken, self.samples.max(1), scramble, rng);
+
+            for (u, v) in &points {
+                // spread the samples over the filter's footprint around the pixel's center
+                let dx = (u * 2.0 - 1.0) * radius;
+                let dy = (v * 2.0 - 1.0) * radius;
+                let color = shade(i as f32 + 0.5 + dx, j as f32 + 0.5 + dy, rng);
+                let weight = self.filter.weight(dx, dy);
+
+                total = total + color * weight;
+                total_weight += weight;
+                for channel in 0..3 {
+                    sum[channel] += color.rgb[channel];
+                    sum_squares[channel] += color.rgb[channel].powi(2);
+                }
+            }
+
+            taken += points.len() as u32;
+            if taken >= self.max_samples || self.converged(sum, sum_squares, taken) {
+                break;
+            }
+        }
+
+        // negative lobes can cancel out all of the weight with few samples
+        let color = match total_weight > 0.0 {
+            true => total * (1.0 / total_weight),
+            false => Color::new_arr(sum) * (1.0 / taken as f32),
+        };
+
+        (color, taken)
+    }
+
+    // whether every channel's mean is known to within the threshold
+    fn converged(&self, sum: [f32; 3], sum_squares: [f32; 3], count: u32) -> bool {
+        if count < 2 {
+            return false;
+        }
+
+        let n = count as f32;
+        (0..3).all(|channel| {
+            let variance = (sum_squares[channel] - sum[channel].powi(2) / n) / (n - 1.0);
+            (variance.max(0.0) / n).sqrt() <= self.variance_threshold
+        })
+    }
+
+    // colors every pixel of the canvas with `shade`, which gets the pixel's coordinates
+    pub fn render<F>(&self, canvas: &mut Canvas, shade: F)
+    where
+        F: Fn(i32, i32) -> Color + Sync,
+    {
+        let tiles_x = (canvas.width + self.tile_size - 1) / self.tile_size;
+        let tiles_y = (canvas.height + self.tile_size - 1) / self.tile_size;
+        let tile_count = (tiles_x * tiles_y) as usize;
+        let next_tile = AtomicUsize::new(0);
+        let (width, height) = (canvas.width, canvas.height);
+
+        let tiles: Vec<Tile> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.threads.min(tile_count.max(1)))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut rendered = Vec::new();
+
+                        loop {
+                            let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                            if index >= tile_count {
+                                break rendered;
+                            }
+
+                            let x = (index as i32 % tiles_x) * self.tile_size;
+                            let y = (index as i32 / tiles_x) * self.tile_size;
+                            let tile_width = self.tile_size.min(width - x);
+                            let tile_height = self.tile_size.min(height - y);
+
+                            let pixels = (y..y + tile_height)
+                                .flat_map(|j| (x..x + tile_width).map(move |i| (i, j)))
+                                .map(|(i, j)| shade(i, j))
+                                .collect();
+
+                            rendered.push(Tile {
+                                x,
+                                y,
+                                width: tile_width,
+                                pixels,
+                            });
+                        }
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        // every tile covers its own part of the canvas, so they are copied in
+        // once all threads are done rather than synchronizing each write
+        for tile in tiles {
+            for (index, color) in tile.pixels.into_iter().enumerate() {
+                let i = tile.x + index as i32 % tile.width;
+                let j = tile.y + index as i32 / tile.width;
+                canvas.write_pixel(i, j, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::util::about_equals;
+
+    fn gradient(i: i32, j: i32) -> Color {
+        Color::new(i as f32, j as f32, (i * j) as f32)
+    }
+
+    #[test]
+    fn renders_every_pixel_once() {
+        // tiles that don't divide the canvas evenly leave partial tiles at the edges
+        for (threads, tile_size) in [(1, 16), (4, 3), (7, 5), (32, 1)] {
+            let mut canvas = Canvas::new(13, 7, Color::new(-1.0, -1.0, -1.0));
+            Renderer::new(threads, tile_size).render(&mut canvas, gradient);
+
+            for i in 0..canvas.width {
+                for j in 0..canvas.height {
+                    assert_eq!(canvas.pixel_at(i, j), gradient(i, j));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn supersampled_edges_are_blended() {
+        let edge = |x: f32, _: f32, _: &mut Rng| match x < 2.5 {
+            true => Color::new(1.0, 1.0, 1.0),
+            false => Color::new(0.0, 0.0, 0.0),
+        };
+
+        let mut renderer = Renderer::new(2, 4);
+        let mut canvas = Canvas::new(5, 1, Color::new(0.0, 0.0, 0.0));
+
+        // a single sample lands on the center of the pixel
+        renderer.render_sampled(&mut canvas, edge);
+        assert_eq!(canvas.pixel_at(2, 0), Color::new(0.0, 0.0, 0.0));
+
+        renderer.samples = 16;
+        assert_eq!(renderer.render_sampled(&mut canvas, edge), 5 * 16);
+        assert_eq!(canvas.pixel_at(1, 0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(canvas.pixel_at(2, 0), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(canvas.pixel_at(3, 0), Color::new(0.0, 0.0, 0.0));
+
+        // wider filters pull in some of the neighbouring pixels
+        for sampler in [Sampler::Jittered, Sampler::Halton, Sampler::Sobol] {
+            renderer.sampler = sampler;
+            renderer.filter = PixelFilter::Gaussian;
+            renderer.render_sampled(&mut canvas, edge);
+
+            let red = canvas.pixel_at(1, 0).rgb[0];
+            assert!(red > 0.5 && red < 1.0, "{:?} gave {}", sampler, red);
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_refines_edges() {
+        let edge = |x: f32, _: f32, _: &mut Rng| match x < 2.5 {
+            true => Color::new(1.0, 1.0, 1.0),
+            false => Color::new(0.0, 0.0, 0.0),
+        };
+
+        let mut renderer = Renderer::new(2, 4);
+        renderer.samples = 4;
+        renderer.max_samples = 64;
+        renderer.sampler = Sampler::Jittered;
+
+        // only the pixel on the edge varies, the other four stop after the first batch
+        let mut canvas = Canvas::new(5, 1, Color::new(0.0, 0.0, 0.0));
+        let rays = renderer.render_sampled(&mut canvas, edge);
+
+        assert_eq!(rays, 4 * 4 + 64);
+        assert_eq!(canvas.pixel_at(0, 0), Color::new(1.0, 1.0, 1.0));
+        assert!(about_equals(canvas.pixel_at(2, 0).rgb[0], 0.5, 0.1));
+
+        // a loose threshold accepts the edge as soon as it has an estimate
+        renderer.variance_threshold = 1.0;
+        assert_eq!(renderer.render_sampled(&mut canvas, edge), 5 * 4);
+    }
+
+    #[test]
+    fn noise_is_the_same_for_any_thread_count() {
+        let noise = |_: f32, _: f32, rng: &mut Rng| Color::new(rng.next_f32(), 0.0, 0.0);
+        let render = |threads, tile_size, seed| {
+            let mut renderer = Renderer::new(threads, tile_size);
+            renderer.samples = 4;
+            renderer.sampler = Sampler::Jittered;
+            renderer.seed = seed;
+
+            let mut canvas = Canvas::new(9, 6, Color::new(0.0, 0.0, 0.0));
+            renderer.render_sampled(&mut canvas, noise);
+            canvas.pixels
+        };
+
+        let expected = render(1, 16, 0);
+        assert_eq!(render(4, 2, 0), expected);
+        assert_eq!(render(3, 5, 0), expected);
+        assert_ne!(render(1, 16, 1), expected);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::math::{Point, Rng, Vector};
+
+use super::{attenuation::Attenuation, world::World, Color, Light, LightSample};
+
+// a point light that only shines within a cone around its direction
+#[derive(Copy, Clone)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    // half angles of the cones, in radians
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+}
+
+impl SpotLight {
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        inner_angle: f32,
+        outer_angle: f32,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+            attenuation: Attenuation::None,
+        }
+    }
+
+    // full strength inside the inner cone, none outside the outer cone and a
+    // smoothstep between the two
+    pub fn falloff(&self, point: Point) -> f32 {
+        let cos = (point - self.position).normalize().dot(self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+
+        if cos >= cos_inner {
+            1.0
+        } else if cos <= cos_outer {
+            0.0
+        } else {
+            let t = (cos - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(
+        &self,
+        _world: &World,
+        point: Point,
+        _time: f32,
+        _rng: &mut Rng,
+    ) -> Vec<LightSample> {
+        vec![LightSample::towards(
+            point,
+            self.position,
+            self.intensity * self.falloff(point),
+            self.attenuation,
+        )]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn falloff() {
+        let light = SpotLight::new(
+            Point::new(0.0, 10.0, 0.0),
+            Vector::new(0.0, -2.0, 0.0),
+            PI / 8.0,
+            PI / 4.0,
+            Color::new(1.0, 1.0, 1.0),
+        );
+
+        assert_eq!(light.falloff(Point::zero()), 1.0);
+        assert_eq!(light.falloff(Point::new(10.0, 0.0, 0.0)), 0.0);
+        assert_eq!(light.falloff(Point::new(0.0, 20.0, 0.0)), 0.0);
+
+        // half way between the cones, measured in cosines
+        let cos = ((PI / 8.0).cos() + (PI / 4.0).cos()) / 2.0;
+        let point = Point::new((1.0 - cos * cos).sqrt(), 10.0 - cos, 0.0);
+        assert!((light.falloff(point) - 0.5).abs() < 0.001);
+    }
+}
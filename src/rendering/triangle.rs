@@ -0,0 +1,111 @@
+use crate::math::{Bounds, Point, Vector};
+
+// a flat triangle in object space, with its edges and normal worked out up front
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Triangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub normal: Vector,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: e2.cross(e1).normalize(),
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::empty()
+            .add_point(self.p1)
+            .add_point(self.p2)
+            .add_point(self.p3)
+    }
+
+    // how far along the ray it crosses the triangle (möller-trumbore), if it does
+    pub fn intersect(&self, origin: Point, direction: Vector) -> Option<f32> {
+        let dir_cross_e2 = direction.cross(self.e2);
+        let determinant = self.e1.dot(dir_cross_e2);
+
+        // the ray runs parallel to the triangle's plane
+        if determinant == 0.0 {
+            return None;
+        }
+
+        let f = 1.0 / determinant;
+        let p1_to_origin = origin - self.p1;
+        let u = f * p1_to_origin.dot(dir_cross_e2);
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross(self.e1);
+        let v = f * direction.dot(origin_cross_e1);
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        Some(f * self.e2.dot(origin_cross_e1))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Point::new(0.0, 1.0, 0.0),
+            Point::new(-1.0, 0.0, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn new_triangle() {
+        let triangle = triangle();
+
+        assert_eq!(triangle.e1, Vector::new(-1.0, -1.0, 0.0));
+        assert_eq!(triangle.e2, Vector::new(1.0, -1.0, 0.0));
+        assert_eq!(triangle.normal, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            triangle.bounds(),
+            Bounds::new(Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 1.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn intersect() {
+        let triangle = triangle();
+        let forward = Vector::new(0.0, 0.0, 1.0);
+
+        assert_eq!(
+            triangle.intersect(Point::new(0.0, 0.5, -2.0), forward),
+            Some(2.0)
+        );
+        // parallel to the plane
+        assert_eq!(
+            triangle.intersect(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 0.0)),
+            None
+        );
+        // past each of the three edges
+        for origin in [
+            Point::new(1.0, 1.0, -2.0),
+            Point::new(-1.0, 1.0, -2.0),
+            Point::new(0.0, -1.0, -2.0),
+        ] {
+            assert_eq!(triangle.intersect(origin, forward), None);
+        }
+    }
+}
@@ -1,8 +1,21 @@
-use super::{Object, PointLight};
+use crate::math::{Bounds, Point, Rng, Vector};
+
+use super::{
+    bvh::Bvh,
+    environment::Environment,
+    intersection::{Computations, Intersection},
+    lighting, Color, Light, Object, Ray,
+};
 
 pub struct World {
     pub objects: Vec<Object>,
-    pub lights: Vec<PointLight>,
+    pub lights: Vec<Box<dyn Light>>,
+    pub environment: Option<Environment>,
+    // how many bounces reflected and refracted rays may take before they are cut off
+    pub max_depth: u32,
+    // built by `build_bvh`, which has to be called again whenever existing objects move.
+    // objects pushed afterwards aren't in it, so it's ignored until it's rebuilt
+    pub bvh: Option<Bvh>,
 }
 
 impl World {
@@ -10,6 +23,698 @@ impl World {
         Self {
             objects: Vec::new(),
             lights: Vec::new(),
+            environment: None,
+            max_depth: 5,
+            bvh: None,
+        }
+    }
+
+    // the box around every object, e.g. for framing the whole scene
+    pub fn bounds(&self) -> Bounds {
+        match self.current_bvh() {
+            Some(bvh) => bvh.bounds(),
+            None => self
+                .objects
+                .iter()
+                .fold(Bounds::empty(), |acc, object| acc.union(object.bounds())),
+        }
+    }
+
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::new(&self.objects));
+    }
+
+    // the bvh, as long as it still covers every object
+    fn current_bvh(&self) -> Option<&Bvh> {
+        self.bvh
+            .as_ref()
+            .filter(|bvh| bvh.len() == self.objects.len())
+    }
+
+    // every intersection of the ray with the world, sorted by time
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut intersections = match self.current_bvh() {
+            Some(bvh) => bvh.intersect(ray, &self.objects),
+            None => {
+                let mut intersections = Vec::new();
+                for object in &self.objects {
+                    ray.intersect_into(object, &mut intersections);
+                }
+                intersections
+            }
+        };
+
+        intersections.sort_by(|a, b| a.time.total_cmp(&b.time));
+        intersections
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: u32, rng: &mut Rng) -> Color {
+        let color = comps.object.color_at(comps.over_point, comps.shutter_time);
+        let surface = self
+            .lights
+            .iter()
+            .fold(Color::new(0.0, 0.0, 0.0), |acc, light| {
+                let samples =
+                    light.visible_samples(self, comps.over_point, comps.shutter_time, rng);
+
+                acc + lighting(
+                    &comps.object.material,
+                    color,
+                    light.as_ref(),
+                    comps.eye,
+                    comps.normal,
+                    &samples,
+                )
+            })
+            + comps.object.material.emission;
+
+        let reflected = self.reflected_color(comps, remaining, rng);
+        let refracted = self.refracted_color(comps, remaining, rng);
+        let material = &comps.object.material;
+
+        // glass-like materials reflect more of the scene the shallower the angle
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            return surface + reflected * reflectance + refracted * (1.0 - reflectance);
+        }
+
+        surface + reflected + refracted
+    }
+
+    // the fraction of light that makes it to the point from `distance` away along
+    // `direction`, each occluder in between lets through its transparency tinted by its color
+    pub fn shadow_transmittance(
+        &self,
+        point: Point,
+        direction: Vector,
+        distance: f32,
+        time: f32,
+    ) -> Color {
+        let ray = Ray::point_vector(point, direction).with_shutter_time(time);
+
+        let mut transmittance = Color::new(1.0, 1.0, 1.0);
+        let mut occluders: Vec<&Object> = Vec::new();
+
+        for intersection in self.intersect(&ray) {
+            let object = intersection.object;
+
+            if intersection.time < 0.0 || intersection.time >= distance || !object.casts_shadow {
+                continue;
+            }
+
+            // a ray passes through both sides of an object, but it only dims the light once
+            if occluders.iter().any(|x| std::ptr::eq(*x, object)) {
+                continue;
+            }
+            occluders.push(object);
+
+            let transparency = object.material.transparency;
+            if transparency == 0.0 {
+                return Color::new(0.0, 0.0, 0.0);
+            }
+
+            let point = ray.position_at(intersection.time);
+            transmittance = transmittance * object.color_at(point, time) * transparency;
+        }
+
+        transmittance
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: u32, rng: &mut Rng) -> Color {
+        let reflective = comps.object.material.reflective;
+
+        if remaining == 0 || reflective == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let ray = Ray::point_vector(comps.over_point, comps.reflect)
+            .with_shutter_time(comps.shutter_time);
+        let color = self
+            .trace(&ray, remaining - 1, rng)
+            .unwrap_or_else(|| self.secondary_background(&ray));
+
+        color * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: u32, rng: &mut Rng) -> Color {
+        let transparency = comps.object.material.transparency;
+
+        if remaining == 0 || transparency == 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        // snell's law, solved for the angle of the refracted ray
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eye.dot(comps.normal);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+
+        // total internal reflection
+        if sin2_t > 1.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = comps.normal * (n_ratio * cos_i - cos_t) - comps.eye * n_ratio;
+        let ray =
+            Ray::point_vector(comps.under_point, direction).with_shutter_time(comps.shutter_time);
+        let color = self
+            .trace(&ray, remaining - 1, rng)
+            .unwrap_or_else(|| self.secondary_background(&ray));
+
+        color * transparency
+    }
+
+    pub fn color_at(&self, ray: &Ray, rng: &mut Rng) -> Color {
+        self.trace(ray, self.max_depth, rng)
+            .unwrap_or_else(|| self.background(ray))
+    }
+
+    // shades the closest hit along the ray, or returns none if it escapes the scene
+    fn trace(&self, ray: &Ray, remaining: u32, rng: &mut Rng) -> Option<Color> {
+        let intersections = self.intersect(ray);
+
+        Intersection::hit(&intersections)
+            .map(|hit| self.shade_hit(&hit.prepare(ray, &intersections), remaining, rng))
+    }
+
+    // the color seen by a reflected or refracted ray that misses every object
+    fn secondary_background(&self, ray: &Ray) -> Color {
+        match &self.environment {
+            Some(environment) if environment.in_reflections => environment.color_at(ray.direction),
+            _ => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    // the color seen by a ray that misses every object
+    pub fn background(&self, ray: &Ray) -> Color {
+        match &self.environment {
+            Some(environment) => environment.color_at(ray.direction),
+            None => Color::new(0.0, 0.0, 0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{f32::consts::PI, sync::Arc};
+
+    use super::*;
+    use crate::math::{util::about_equals, Matrix4x4, Point};
+    use crate::rendering::{
+        area_light::AreaLight,
+        attenuation::Attenuation,
+        directional_light::DirectionalLight,
+        environment::EnvironmentMap,
+        pattern::{Addressing, Filter, Texture},
+        spot_light::SpotLight,
+        Canvas, LightSample, Material, PointLight,
+    };
+
+    fn default_world() -> World {
+        let mut world = World::new();
+        world.lights.push(Box::new(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )));
+
+        let mut outer = Object::new_sphere(
+            Material::new(Color::new(0.8, 1.0, 0.6), 0.1, 0.7, 0.2, 200.0),
+            Matrix4x4::identity(),
+        );
+        let mut inner = Object::new_sphere(
+            Material::default(),
+            Matrix4x4::identity().scale(0.5, 0.5, 0.5),
+        );
+
+        for object in [&mut outer, &mut inner] {
+            object.calc_inverse_transform();
+            object.calc_inverse_transpose_transform();
         }
+
+        world.objects.push(outer);
+        world.objects.push(inner);
+        world
+    }
+
+    #[test]
+    fn intersect_sorted() {
+        let world = default_world();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+        let times: Vec<f32> = world.intersect(&ray).iter().map(|x| x.time).collect();
+
+        assert_eq!(times, [4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn world_bounds() {
+        let mut world = default_world();
+        let expected = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+
+        assert_eq!(world.bounds(), expected);
+        world.build_bvh();
+        assert_eq!(world.bounds(), expected);
+    }
+
+    #[test]
+    fn intersect_through_bvh() {
+        let mut world = default_world();
+        world.build_bvh();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+        let times: Vec<f32> = world.intersect(&ray).iter().map(|x| x.time).collect();
+
+        assert_eq!(times, [4.0, 4.5, 5.5, 6.0]);
+        assert!(world
+            .intersect(&Ray::new(0.0, 2.0, -5.0, 0.0, 0.0, 1.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn objects_pushed_after_the_bvh_are_still_hit() {
+        let mut world = default_world();
+        world.build_bvh();
+        world.objects.push(Object::new_sphere(
+            Material::default(),
+            Matrix4x4::identity().translate(0.0, 2.0, 0.0),
+        ));
+        let ray = Ray::new(0.0, 2.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(world.intersect(&ray).len(), 2);
+        assert_eq!(world.bounds().max.xyz[1], 3.0);
+    }
+
+    #[test]
+    fn color_at_hit() {
+        let world = default_world();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(
+            world.color_at(&ray, &mut Rng::new(0)).round(0.001),
+            Color::new(0.38066, 0.47583, 0.2855).round(0.001)
+        );
+    }
+
+    #[test]
+    fn color_at_miss_uses_environment() {
+        let mut world = default_world();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 1.0, 0.0);
+
+        assert_eq!(
+            world.color_at(&ray, &mut Rng::new(0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+
+        let sky = Color::new(0.2, 0.4, 0.8);
+        let texture = Texture::new(
+            Arc::new(Canvas::new(4, 2, sky)),
+            Filter::Bilinear,
+            Addressing::Wrap,
+        );
+        world.environment = Some(Environment::new(EnvironmentMap::Equirectangular(texture)));
+
+        assert_eq!(world.color_at(&ray, &mut Rng::new(0)), sky);
+    }
+
+    fn shadow(world: &World, point: Point, light: Point) -> Color {
+        let sample =
+            LightSample::towards(point, light, Color::new(1.0, 1.0, 1.0), Attenuation::None);
+        world.shadow_transmittance(point, sample.direction, sample.distance, 0.0)
+    }
+
+    #[test]
+    fn shadows() {
+        let world = default_world();
+        let light = Point::new(-10.0, 10.0, -10.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let black = Color::new(0.0, 0.0, 0.0);
+
+        assert_eq!(shadow(&world, Point::new(0.0, 10.0, 0.0), light), white);
+        assert_eq!(shadow(&world, Point::new(10.0, -10.0, 10.0), light), black);
+        assert_eq!(shadow(&world, Point::new(-20.0, 20.0, -20.0), light), white);
+        assert_eq!(shadow(&world, Point::new(-2.0, 2.0, -2.0), light), white);
+    }
+
+    #[test]
+    fn translucent_shadows() {
+        let mut world = default_world();
+        let point = Point::new(10.0, -10.0, 10.0);
+        let light = Point::new(-10.0, 10.0, -10.0);
+
+        // only the outer sphere is in the way, since the inner one is inside it
+        world.objects[1].casts_shadow = false;
+        world.objects[0].material.color = Color::new(1.0, 0.5, 0.5);
+        world.objects[0].material.transparency = 0.5;
+
+        assert_eq!(shadow(&world, point, light), Color::new(0.5, 0.25, 0.25));
+
+        world.objects[0].casts_shadow = false;
+
+        assert_eq!(shadow(&world, point, light), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn area_light_soft_shadows() {
+        let mut world = default_world();
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let light = AreaLight::new(
+            corner,
+            Vector::new(1.0, 0.0, 0.0),
+            2,
+            Vector::new(0.0, 1.0, 0.0),
+            2,
+            Color::new(1.0, 1.0, 1.0),
+        );
+        let mut rng = Rng::new(0);
+
+        let visible = |samples: Vec<LightSample>| {
+            samples
+                .iter()
+                .filter(|x| x.transmittance.rgb[0] > 0.0)
+                .count()
+        };
+
+        // fully lit, fully shadowed, and in the penumbra where only part of the light shows
+        let lit = light.visible_samples(&world, Point::new(0.0, 0.0, -2.0), 0.0, &mut rng);
+        let shadowed = light.visible_samples(&world, Point::new(0.0, 0.0, 2.0), 0.0, &mut rng);
+        let penumbra = light.visible_samples(&world, Point::new(1.65, 0.0, 3.0), 0.0, &mut rng);
+
+        assert_eq!(visible(lit), 4);
+        assert_eq!(visible(shadowed), 0);
+        assert!((1..4).contains(&visible(penumbra)));
+
+        world.lights = vec![Box::new(light)];
+        let color = world.color_at(&Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0), &mut rng);
+        assert!(color.rgb.iter().all(|c| c.is_finite() && *c > 0.0));
+    }
+
+    #[test]
+    fn spot_light_cone() {
+        let mut world = default_world();
+        world.lights = vec![Box::new(SpotLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Vector::new(0.0, 0.0, 1.0),
+            PI / 90.0,
+            PI / 60.0,
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+
+        // the outer sphere is lit head on, but a ray aimed off to the side of
+        // the cone only picks up the ambient term
+        let lit = world.color_at(&Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0), &mut Rng::new(0));
+        let unlit = world.color_at(&Ray::new(0.0, 0.9, -5.0, 0.0, 0.0, 1.0), &mut Rng::new(0));
+
+        assert!(lit.rgb[1] > 0.5);
+        assert_eq!(
+            unlit.round(0.0001),
+            Color::new(0.08, 0.1, 0.06).round(0.0001)
+        );
+    }
+
+    #[test]
+    fn directional_light() {
+        let mut world = default_world();
+        world.lights = vec![Box::new(DirectionalLight::new(
+            Vector::new(0.0, 0.0, 1.0),
+            Color::new(1.0, 1.0, 1.0),
+        ))];
+
+        // the light comes from the same direction everywhere, however far away the point is
+        let near = world.lights[0].visible_samples(
+            &world,
+            Point::new(0.0, 0.0, -2.0),
+            0.0,
+            &mut Rng::new(0),
+        );
+        let far = world.lights[0].visible_samples(
+            &world,
+            Point::new(0.0, 0.0, -1e6),
+            0.0,
+            &mut Rng::new(0),
+        );
+        let behind = world.lights[0].visible_samples(
+            &world,
+            Point::new(0.0, 0.0, 2.0),
+            0.0,
+            &mut Rng::new(0),
+        );
+
+        assert_eq!(near[0].direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(near[0].transmittance, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(far[0].direction, near[0].direction);
+        assert_eq!(behind[0].transmittance, Color::new(0.0, 0.0, 0.0));
+
+        let color = world.color_at(&Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0), &mut Rng::new(0));
+        assert_eq!(
+            color.round(0.0001),
+            Color::new(0.84, 1.0, 0.68).round(0.0001)
+        );
+    }
+
+    #[test]
+    fn attenuated_lights() {
+        let mut world = default_world();
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+        let mut light = PointLight::new(Point::new(0.0, 0.0, -5.0), Color::new(1.0, 1.0, 1.0));
+
+        // the hit is 4 units from the light
+        for (attenuation, factor) in [
+            (Attenuation::None, 1.0),
+            (Attenuation::Linear, 0.25),
+            (Attenuation::InverseSquare, 0.0625),
+        ] {
+            light.attenuation = attenuation;
+            world.lights = vec![Box::new(light)];
+
+            let samples = world.lights[0].visible_samples(
+                &world,
+                Point::new(0.0, 0.0, -1.0),
+                0.0,
+                &mut Rng::new(0),
+            );
+            let color = world.color_at(&ray, &mut Rng::new(0));
+            let ambient = Color::new(0.08, 0.1, 0.06);
+            let direct = Color::new(0.76, 0.9, 0.62) * factor;
+
+            assert_eq!(samples[0].intensity, Color::new(factor, factor, factor));
+            let expected = ambient + direct;
+
+            assert!((0..3).all(|i| about_equals(color.rgb[i], expected.rgb[i], 0.001)));
+        }
+    }
+
+    #[test]
+    fn emissive_objects() {
+        let mut world = World::new();
+        let mut sign = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        sign.material.emission = Color::new(1.0, 0.5, 0.0);
+        sign.calc_inverse_transform();
+        sign.calc_inverse_transpose_transform();
+        world.objects.push(sign);
+
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(
+            world.color_at(&ray, &mut Rng::new(0)),
+            Color::new(1.0, 0.5, 0.0)
+        );
+    }
+
+    #[test]
+    fn shade_hit_in_shadow() {
+        let mut world = World::new();
+        world.lights.push(Box::new(PointLight::new(
+            Point::new(0.0, 0.0, -10.0),
+            Color::new(1.0, 1.0, 1.0),
+        )));
+
+        let mut first = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        let mut second = Object::new_sphere(
+            Material::default(),
+            Matrix4x4::identity().translate(0.0, 0.0, 10.0),
+        );
+
+        for object in [&mut first, &mut second] {
+            object.calc_inverse_transform();
+            object.calc_inverse_transpose_transform();
+        }
+
+        world.objects.push(first);
+        world.objects.push(second);
+
+        let ray = Ray::new(0.0, 0.0, 5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(
+            world.color_at(&ray, &mut Rng::new(0)),
+            Color::new(0.1, 0.1, 0.1)
+        );
+    }
+
+    #[test]
+    fn reflected_color() {
+        let mut world = default_world();
+        let mut mirror = Object::new_sphere(
+            Material::new(Color::new(0.0, 0.0, 0.0), 0.0, 0.0, 0.0, 200.0),
+            Matrix4x4::identity().translate(0.0, 0.0, 5.0),
+        );
+        mirror.material.reflective = 0.5;
+        mirror.calc_inverse_transform();
+        mirror.calc_inverse_transpose_transform();
+        world.objects.push(mirror);
+
+        // the mirror faces the default world's spheres, so it reflects them straight back
+        let ray = Ray::new(0.0, 0.0, 2.0, 0.0, 0.0, 1.0);
+        let intersections = world.intersect(&ray);
+        let comps = Intersection::hit(&intersections)
+            .unwrap()
+            .prepare(&ray, &intersections);
+        let reflected = world.color_at(
+            &Ray::point_vector(comps.over_point, comps.reflect),
+            &mut Rng::new(0),
+        );
+
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 4.0));
+        assert_eq!(
+            world.reflected_color(&comps, 5, &mut Rng::new(0)),
+            reflected * 0.5
+        );
+        assert_eq!(
+            world.reflected_color(&comps, 0, &mut Rng::new(0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+        assert_eq!(world.color_at(&ray, &mut Rng::new(0)), reflected * 0.5);
+    }
+
+    #[test]
+    fn total_internal_reflection() {
+        let mut world = default_world();
+        world.objects[0].material.transparency = 1.0;
+        world.objects[0].material.refractive_index = 1.5;
+
+        let ray = Ray::new(0.0, 0.0, 2.0_f32.sqrt() / 2.0, 0.0, 1.0, 0.0);
+        let intersections = world.intersect(&ray);
+        let comps = Intersection::hit(&intersections)
+            .unwrap()
+            .prepare(&ray, &intersections);
+
+        assert_eq!(
+            world.refracted_color(&comps, 5, &mut Rng::new(0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn refracted_color_sees_through() {
+        let mut world = World::new();
+        let sky = Color::new(0.2, 0.4, 0.8);
+        let texture = Texture::new(
+            Arc::new(Canvas::new(1, 1, sky)),
+            Filter::Nearest,
+            Addressing::Clamp,
+        );
+        world.environment = Some(Environment::new(EnvironmentMap::Equirectangular(texture)));
+
+        // a fully transparent sphere with the index of air bends nothing
+        let mut glass = Object::new_sphere(
+            Material::new(Color::new(1.0, 1.0, 1.0), 0.0, 0.0, 0.0, 200.0),
+            Matrix4x4::identity(),
+        );
+        glass.material.transparency = 1.0;
+        glass.calc_inverse_transform();
+        glass.calc_inverse_transpose_transform();
+        world.objects.push(glass);
+
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+
+        assert_eq!(world.color_at(&ray, &mut Rng::new(0)), sky);
+
+        // like reflections, refractions only see the environment when asked to
+        world.environment.as_mut().unwrap().in_reflections = false;
+        assert_eq!(
+            world.color_at(&ray, &mut Rng::new(0)),
+            Color::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn fresnel_blend() {
+        let mut world = World::new();
+        let sky = Color::new(0.2, 0.4, 0.8);
+        let texture = Texture::new(
+            Arc::new(Canvas::new(1, 1, sky)),
+            Filter::Nearest,
+            Addressing::Clamp,
+        );
+        world.environment = Some(Environment::new(EnvironmentMap::Equirectangular(texture)));
+
+        let mut glass = Object::new_sphere(
+            Material::new(Color::new(1.0, 1.0, 1.0), 0.0, 0.0, 0.0, 200.0),
+            Matrix4x4::identity(),
+        );
+        glass.material.reflective = 1.0;
+        glass.material.transparency = 1.0;
+        glass.material.refractive_index = 1.5;
+        glass.calc_inverse_transform();
+        glass.calc_inverse_transpose_transform();
+        world.objects.push(glass);
+
+        // at a grazing angle glass reflects far more than the 4% it does head on
+        let ray = Ray::new(0.0, 0.99, -2.0, 0.0, 0.0, 1.0);
+        let intersections = world.intersect(&ray);
+        let comps = Intersection::hit(&intersections)
+            .unwrap()
+            .prepare(&ray, &intersections);
+        let reflectance = comps.schlick();
+        let expected = world.reflected_color(&comps, world.max_depth, &mut Rng::new(0))
+            * reflectance
+            + world.refracted_color(&comps, world.max_depth, &mut Rng::new(0))
+                * (1.0 - reflectance);
+
+        assert!(reflectance > 0.4);
+        assert_eq!(
+            world.shade_hit(&comps, world.max_depth, &mut Rng::new(0)),
+            expected
+        );
+    }
+
+    #[test]
+    fn mirrors_terminate() {
+        let mut world = World::new();
+        world.lights.push(Box::new(PointLight::new(
+            Point::zero(),
+            Color::new(1.0, 1.0, 1.0),
+        )));
+
+        // looking out from inside a mirrored sphere bounces back and forth forever
+        let mut mirror = Object::new_sphere(Material::default(), Matrix4x4::identity());
+        mirror.material.reflective = 1.0;
+        mirror.calc_inverse_transform();
+        mirror.calc_inverse_transpose_transform();
+        world.objects.push(mirror);
+
+        let color = world.color_at(&Ray::new(0.0, 0.0, 0.0, 0.0, 1.0, 0.0), &mut Rng::new(0));
+
+        assert!(color.rgb.iter().all(|c| c.is_finite()));
+    }
+
+    #[test]
+    fn moving_objects_are_hit_where_they_are_at_the_shutter_time() {
+        fn times(world: &World, ray: Ray) -> Vec<f32> {
+            world.intersect(&ray).iter().map(|x| x.time).collect()
+        }
+
+        let mut world = default_world();
+        world.objects[0].set_end_transform(Matrix4x4::identity().translate(0.0, 4.0, 0.0));
+        world.objects.truncate(1);
+
+        let ray = Ray::new(0.0, 0.0, -5.0, 0.0, 0.0, 1.0);
+        assert_eq!(times(&world, ray), [4.0, 6.0]);
+        assert!(times(&world, ray.with_shutter_time(1.0)).is_empty());
+
+        let halfway = Ray::new(0.0, 2.0, -5.0, 0.0, 0.0, 1.0).with_shutter_time(0.5);
+        assert_eq!(times(&world, halfway), [4.0, 6.0]);
+
+        // the bvh boxes the whole sweep rather than just where the sphere starts
+        world.build_bvh();
+        let end = Ray::new(0.0, 4.0, -5.0, 0.0, 0.0, 1.0).with_shutter_time(1.0);
+        assert_eq!(times(&world, end), [4.0, 6.0]);
     }
 }